@@ -0,0 +1,78 @@
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use libconfig_rs::Value;
+use serde::Deserialize;
+use std::{fmt::Write, str::FromStr};
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Scenes {
+    scenes: Vec<Scene>,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Scene {
+    scene_id: u32,
+    name: String,
+    position: Vec<f64>,
+    tags: Vec<String>,
+    enabled: bool,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct BorrowedScenes<'a> {
+    #[serde(borrow)]
+    scenes: Vec<BorrowedScene<'a>>,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct BorrowedScene<'a> {
+    scene_id: u32,
+    name: &'a str,
+    position: Vec<f64>,
+    tags: Vec<&'a str>,
+    enabled: bool,
+}
+
+/// A scene file of about a megabyte.
+fn scenes() -> String {
+    let mut config = String::from("config : {\n  scenes = (\n");
+    for i in 0..10_000 {
+        writeln!(
+            config,
+            "    {{ scene_id = {i}; name = \"scene {i}\"; position = [ {i}.5, 2.0, -3.25 ]; \
+             tags = ( \"outdoor\", \"level {}\" ); enabled = {}; }},",
+            i % 10,
+            i % 2 == 0
+        )
+        .unwrap();
+    }
+    config.push_str("  );\n};\n");
+    config
+}
+
+fn bench_deserialize(c: &mut Criterion) {
+    let config = scenes();
+    let mut group = c.benchmark_group("deserialize");
+
+    group.bench_function("from_str", |b| {
+        b.iter(|| libconfig_rs::from_str::<Scenes>(black_box(&config)).unwrap())
+    });
+    group.bench_function("from_str borrowed", |b| {
+        b.iter(|| libconfig_rs::from_str::<BorrowedScenes>(black_box(&config)).unwrap())
+    });
+    // Parsing into a `Value` first, then deserializing from its tokens, as
+    // `from_str` used to.
+    group.bench_function("from_value", |b| {
+        b.iter(|| {
+            let value = Value::from_str(black_box(&config)).unwrap();
+            libconfig_rs::from_value::<Scenes>(value).unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_deserialize);
+criterion_main!(benches);
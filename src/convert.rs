@@ -0,0 +1,398 @@
+use crate::{ArrayType, IntFormat, LookupErrorKind, Value};
+use indexmap::IndexMap;
+use std::{borrow::Cow, collections::HashMap, convert::Infallible, hash::Hash, path::PathBuf};
+
+/// Whether reading a setting as a number may convert between integers and
+/// floats, like libconfig's `CONFIG_OPTION_AUTOCONVERT`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Conversion {
+    /// Integers only read as integers and floats only as floats.
+    #[default]
+    Strict,
+    /// Integers also read as floats, and floats as integers by truncation.
+    Auto,
+}
+
+/// Types a setting can be read as with [`Value::lookup_value`].
+pub trait FromValue: Sized {
+    fn from_value(value: &Value, conversion: Conversion) -> Result<Self, LookupErrorKind>;
+}
+
+fn wrong_type(expected: &'static str, value: &Value) -> LookupErrorKind {
+    LookupErrorKind::WrongType {
+        expected,
+        actual: value.type_name(),
+    }
+}
+
+macro_rules! impl_from_value_int {
+    ($($ty:ty),*) => {$(
+        impl FromValue for $ty {
+            fn from_value(value: &Value, conversion: Conversion) -> Result<Self, LookupErrorKind> {
+                let out_of_range = |v: &dyn ToString| LookupErrorKind::OutOfRange {
+                    expected: stringify!($ty),
+                    value: v.to_string(),
+                };
+                match value {
+                    Value::Int(v, _) => <$ty>::try_from(*v).map_err(|_| out_of_range(v)),
+                    Value::Int64(v, _) => <$ty>::try_from(*v).map_err(|_| out_of_range(v)),
+                    Value::UInt64(v, _) => <$ty>::try_from(*v).map_err(|_| out_of_range(v)),
+                    Value::Float(v) if conversion == Conversion::Auto => {
+                        // `MAX as f64` rounds up for the 64-bit and wider types,
+                        // so the range is checked on the exact integer instead.
+                        let t = v.trunc();
+                        if t >= 0.0 && t < u128::MAX as f64 {
+                            <$ty>::try_from(t as u128).map_err(|_| out_of_range(v))
+                        } else if t < 0.0 && t >= i128::MIN as f64 {
+                            <$ty>::try_from(t as i128).map_err(|_| out_of_range(v))
+                        } else {
+                            Err(out_of_range(v))
+                        }
+                    }
+                    _ => Err(wrong_type(stringify!($ty), value)),
+                }
+            }
+        }
+    )*};
+}
+
+impl_from_value_int!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+
+impl FromValue for f64 {
+    fn from_value(value: &Value, conversion: Conversion) -> Result<Self, LookupErrorKind> {
+        match (value, conversion) {
+            (Value::Float(v), _) => Ok(*v),
+            (Value::Int(v, _), Conversion::Auto) => Ok(f64::from(*v)),
+            (Value::Int64(v, _), Conversion::Auto) => Ok(*v as f64),
+            (Value::UInt64(v, _), Conversion::Auto) => Ok(*v as f64),
+            _ => Err(wrong_type("f64", value)),
+        }
+    }
+}
+
+impl FromValue for f32 {
+    fn from_value(value: &Value, conversion: Conversion) -> Result<Self, LookupErrorKind> {
+        let v = f64::from_value(value, conversion).map_err(|_| wrong_type("f32", value))?;
+        if v.is_finite() && v.abs() > f64::from(f32::MAX) {
+            return Err(LookupErrorKind::OutOfRange {
+                expected: "f32",
+                value: v.to_string(),
+            });
+        }
+        Ok(v as f32)
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: &Value, _conversion: Conversion) -> Result<Self, LookupErrorKind> {
+        value
+            .as_bool()
+            .copied()
+            .ok_or_else(|| wrong_type("bool", value))
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: &Value, _conversion: Conversion) -> Result<Self, LookupErrorKind> {
+        value
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| wrong_type("string", value))
+    }
+}
+
+impl FromValue for char {
+    fn from_value(value: &Value, _conversion: Conversion) -> Result<Self, LookupErrorKind> {
+        let s = value.as_str().ok_or_else(|| wrong_type("char", value))?;
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(LookupErrorKind::OutOfRange {
+                expected: "char",
+                value: format!("{s:?}"),
+            }),
+        }
+    }
+}
+
+impl FromValue for PathBuf {
+    fn from_value(value: &Value, _conversion: Conversion) -> Result<Self, LookupErrorKind> {
+        value
+            .as_str()
+            .map(PathBuf::from)
+            .ok_or_else(|| wrong_type("path", value))
+    }
+}
+
+impl FromValue for Value {
+    fn from_value(value: &Value, _conversion: Conversion) -> Result<Self, LookupErrorKind> {
+        Ok(value.clone())
+    }
+}
+
+/// Lets collections of `Value` be converted like collections of other types.
+impl From<Infallible> for LookupErrorKind {
+    fn from(never: Infallible) -> Self {
+        match never {}
+    }
+}
+
+macro_rules! impl_try_from_value {
+    ($($ty:ty),*) => {$(
+        impl TryFrom<&Value> for $ty {
+            type Error = LookupErrorKind;
+
+            fn try_from(value: &Value) -> Result<Self, Self::Error> {
+                <$ty>::from_value(value, Conversion::Strict)
+            }
+        }
+
+        impl TryFrom<Value> for $ty {
+            type Error = LookupErrorKind;
+
+            fn try_from(value: Value) -> Result<Self, Self::Error> {
+                <$ty>::from_value(&value, Conversion::Strict)
+            }
+        }
+    )*};
+}
+
+impl_try_from_value!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64, bool, char, PathBuf
+);
+
+impl TryFrom<&Value> for String {
+    type Error = LookupErrorKind;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        String::from_value(value, Conversion::Strict)
+    }
+}
+
+impl TryFrom<Value> for String {
+    type Error = LookupErrorKind;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::String(s) => Ok(s),
+            _ => Err(wrong_type("string", &value)),
+        }
+    }
+}
+
+impl<T: TryFrom<Value>> TryFrom<Value> for Vec<T>
+where
+    LookupErrorKind: From<T::Error>,
+{
+    type Error = LookupErrorKind;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Array(a, _) => a.into_iter().map(|v| Ok(T::try_from(v)?)).collect(),
+            _ => Err(wrong_type("array or list", &value)),
+        }
+    }
+}
+
+impl<T: TryFrom<Value>> TryFrom<Value> for IndexMap<String, T>
+where
+    LookupErrorKind: From<T::Error>,
+{
+    type Error = LookupErrorKind;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Object(o) => o
+                .into_iter()
+                .map(|(k, v)| Ok((k, T::try_from(v)?)))
+                .collect(),
+            _ => Err(wrong_type("group", &value)),
+        }
+    }
+}
+
+impl<T: TryFrom<Value>> TryFrom<Value> for HashMap<String, T>
+where
+    LookupErrorKind: From<T::Error>,
+{
+    type Error = LookupErrorKind;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Object(o) => o
+                .into_iter()
+                .map(|(k, v)| Ok((k, T::try_from(v)?)))
+                .collect(),
+            _ => Err(wrong_type("group", &value)),
+        }
+    }
+}
+
+macro_rules! impl_from_int {
+    ($($ty:ty),*) => {$(
+        impl From<$ty> for Value {
+            fn from(v: $ty) -> Self {
+                Value::Int(v.into(), IntFormat::Decimal)
+            }
+        }
+    )*};
+}
+
+impl_from_int!(i8, i16, i32, u8, u16);
+
+macro_rules! impl_from_int64 {
+    ($($ty:ty),*) => {$(
+        impl From<$ty> for Value {
+            fn from(v: $ty) -> Self {
+                match i64::try_from(v) {
+                    Ok(v) => Value::Int64(v, IntFormat::Decimal),
+                    // Only reachable for unsigned values above `i64::MAX`.
+                    Err(_) => Value::UInt64(v as u64, IntFormat::Decimal),
+                }
+            }
+        }
+    )*};
+}
+
+impl_from_int64!(i64, isize, u32, u64, usize);
+
+macro_rules! impl_try_from_int128 {
+    ($($ty:ty),*) => {$(
+        /// Fails for values outside both the int64 and the uint64 range.
+        impl TryFrom<$ty> for Value {
+            type Error = LookupErrorKind;
+
+            fn try_from(v: $ty) -> Result<Self, Self::Error> {
+                if let Ok(v) = i64::try_from(v) {
+                    Ok(Value::Int64(v, IntFormat::Decimal))
+                } else if let Ok(v) = u64::try_from(v) {
+                    Ok(Value::UInt64(v, IntFormat::Decimal))
+                } else {
+                    Err(LookupErrorKind::OutOfRange {
+                        expected: "int64",
+                        value: v.to_string(),
+                    })
+                }
+            }
+        }
+    )*};
+}
+
+impl_try_from_int128!(i128, u128);
+
+impl From<f32> for Value {
+    fn from(v: f32) -> Self {
+        Value::Float(v.into())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(v: f64) -> Self {
+        Value::Float(v)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(v: bool) -> Self {
+        Value::Bool(v)
+    }
+}
+
+impl From<char> for Value {
+    fn from(v: char) -> Self {
+        Value::String(v.into())
+    }
+}
+
+impl From<&str> for Value {
+    fn from(v: &str) -> Self {
+        Value::String(v.into())
+    }
+}
+
+impl From<String> for Value {
+    fn from(v: String) -> Self {
+        Value::String(v)
+    }
+}
+
+impl From<Cow<'_, str>> for Value {
+    fn from(v: Cow<'_, str>) -> Self {
+        Value::String(v.into_owned())
+    }
+}
+
+/// Builds an array when every element is a scalar of the same type, as
+/// libconfig requires of arrays, and a list otherwise.
+pub(crate) fn sequence(elements: Vec<Value>) -> Value {
+    let homogeneous = elements
+        .iter()
+        .all(|v| v.is_scalar() && v.type_name() == elements[0].type_name());
+    let array_type = if homogeneous {
+        ArrayType::Array
+    } else {
+        ArrayType::List
+    };
+    Value::Array(elements, array_type)
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(v: Vec<T>) -> Self {
+        sequence(v.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Clone + Into<Value>> From<&[T]> for Value {
+    fn from(v: &[T]) -> Self {
+        sequence(v.iter().cloned().map(Into::into).collect())
+    }
+}
+
+impl<K: Into<String>, V: Into<Value>> From<IndexMap<K, V>> for Value {
+    fn from(v: IndexMap<K, V>) -> Self {
+        Value::Object(v.into_iter().map(|(k, v)| (k.into(), v.into())).collect())
+    }
+}
+
+impl<K: Into<String> + Eq + Hash, V: Into<Value>> From<HashMap<K, V>> for Value {
+    fn from(v: HashMap<K, V>) -> Self {
+        Value::Object(v.into_iter().map(|(k, v)| (k.into(), v.into())).collect())
+    }
+}
+
+macro_rules! impl_tuple {
+    ($len:literal => $($name:ident),+) => {
+        impl<$($name: Into<Value>),+> From<($($name,)+)> for Value {
+            #[allow(non_snake_case)]
+            fn from(($($name,)+): ($($name,)+)) -> Self {
+                sequence(vec![$($name.into()),+])
+            }
+        }
+
+        impl<$($name: TryFrom<Value>),+> TryFrom<Value> for ($($name,)+)
+        where
+            $(LookupErrorKind: From<$name::Error>),+
+        {
+            type Error = LookupErrorKind;
+
+            fn try_from(value: Value) -> Result<Self, Self::Error> {
+                match value {
+                    Value::Array(a, _) if a.len() == $len => {
+                        let mut elements = a.into_iter();
+                        Ok(($($name::try_from(elements.next().unwrap())?,)+))
+                    }
+                    _ => Err(wrong_type(concat!("array or list of ", $len, " elements"), &value)),
+                }
+            }
+        }
+    };
+}
+
+impl_tuple!(1 => A);
+impl_tuple!(2 => A, B);
+impl_tuple!(3 => A, B, C);
+impl_tuple!(4 => A, B, C, D);
+impl_tuple!(5 => A, B, C, D, E);
+impl_tuple!(6 => A, B, C, D, E, F);
@@ -0,0 +1,358 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// The 1-based line and column, counted in characters, of byte `offset` of
+/// `input`.
+pub(crate) fn line_column(input: &str, offset: usize) -> (usize, usize) {
+    let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+    (
+        input[..offset].matches('\n').count() + 1,
+        input[line_start..offset].chars().count() + 1,
+    )
+}
+
+/// An error found while reading libconfig text, with its location in the input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError(Box<Inner>);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Inner {
+    file: Option<PathBuf>,
+    offset: usize,
+    line: usize,
+    column: usize,
+    snippet: String,
+    context: Vec<&'static str>,
+    expected: Vec<String>,
+    reason: Option<String>,
+    at_end: bool,
+    located: bool,
+    first_location: Option<(usize, usize)>,
+}
+
+impl ParseError {
+    /// Builds an error at byte `offset` of `input`.
+    pub(crate) fn new(
+        input: &str,
+        offset: usize,
+        context: Vec<&'static str>,
+        expected: Vec<String>,
+        reason: Option<String>,
+    ) -> Self {
+        let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[offset..]
+            .find('\n')
+            .map_or(input.len(), |i| offset + i);
+        let (line, column) = line_column(input, offset);
+
+        ParseError(Box::new(Inner {
+            file: None,
+            offset,
+            line,
+            column,
+            snippet: input[line_start..line_end].trim_end_matches('\r').into(),
+            context,
+            expected,
+            reason,
+            at_end: offset == input.len(),
+            located: true,
+            first_location: None,
+        }))
+    }
+
+    /// Records where a duplicated setting was first set, at byte `offset` of
+    /// `input`.
+    pub(crate) fn with_first_location(mut self, input: &str, offset: usize) -> Self {
+        let first = Self::new(input, offset, Vec::new(), Vec::new(), None);
+        self.0.first_location = Some((first.line(), first.column()));
+        self
+    }
+
+    /// An error that is not tied to a position in the input, such as a file
+    /// that could not be read.
+    pub(crate) fn unlocated(reason: String) -> Self {
+        let mut e = Self::new("", 0, Vec::new(), Vec::new(), Some(reason));
+        e.0.located = false;
+        e
+    }
+
+    pub(crate) fn with_file(mut self, file: &Path) -> Self {
+        self.0.file.get_or_insert_with(|| file.into());
+        self
+    }
+
+    /// The file the error was found in, when the input was read from a file or
+    /// the error is inside an `@include`d one.
+    pub fn file(&self) -> Option<&Path> {
+        self.0.file.as_deref()
+    }
+
+    /// Byte offset of the error in the input.
+    pub fn offset(&self) -> usize {
+        self.0.offset
+    }
+
+    /// 1-based line of the error.
+    pub fn line(&self) -> usize {
+        self.0.line
+    }
+
+    /// 1-based column of the error, counted in characters.
+    pub fn column(&self) -> usize {
+        self.0.column
+    }
+
+    /// The line of input containing the error.
+    pub fn snippet(&self) -> &str {
+        &self.0.snippet
+    }
+
+    /// What the parser was in the middle of reading, outermost first,
+    /// e.g. `["map", "array"]`.
+    pub fn context(&self) -> &[&'static str] {
+        &self.0.context
+    }
+
+    /// The tokens that would have been accepted at the error position.
+    pub fn expected(&self) -> &[String] {
+        &self.0.expected
+    }
+
+    /// A description of the error beyond the expected tokens, e.g. why an
+    /// `@include` failed.
+    pub fn reason(&self) -> Option<&str> {
+        self.0.reason.as_deref()
+    }
+
+    /// For a duplicate setting, the 1-based line and column where the name
+    /// was first set.
+    pub fn first_location(&self) -> Option<(usize, usize)> {
+        self.0.first_location
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let e = &self.0;
+
+        if let Some(file) = &e.file {
+            write!(f, "{}, ", file.display())?;
+        }
+        if !e.located {
+            return write!(f, "{}", e.reason.as_deref().unwrap_or_default());
+        }
+        write!(f, "line {}, column {}: ", e.line, e.column)?;
+
+        if let Some(reason) = &e.reason {
+            write!(f, "{reason}")?;
+            if let Some((line, column)) = e.first_location {
+                write!(f, ", first set at line {line}, column {column}")?;
+            }
+        } else {
+            let expected = match e.expected.as_slice() {
+                [] => None,
+                [expected] => Some(format!("expected {expected}")),
+                expected => Some(format!("expected one of {}", expected.join(", "))),
+            };
+            match (e.at_end, expected) {
+                (true, Some(expected)) => write!(f, "unexpected end of input, {expected}")?,
+                (true, None) => write!(f, "unexpected end of input")?,
+                (false, Some(expected)) => write!(f, "{expected}")?,
+                (false, None) => write!(f, "unexpected input")?,
+            }
+        }
+
+        let gutter = " ".repeat(e.line.to_string().len());
+        let marker = e
+            .snippet
+            .chars()
+            .take(e.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+
+        writeln!(f)?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{} | {}", e.line, e.snippet)?;
+        write!(f, "{gutter} | {marker}^")?;
+
+        if !e.context.is_empty() {
+            writeln!(f)?;
+            write!(f, "{gutter} = while parsing {}", e.context.join(" > "))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Why a setting path could not be followed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LookupErrorKind {
+    /// The group has no setting with this name.
+    MissingKey,
+    /// The index is past the end of the list, array or group.
+    IndexOutOfBounds { index: usize, len: usize },
+    /// A name was looked up in something other than a group.
+    NotAGroup,
+    /// An index was looked up in a scalar.
+    NotAggregate,
+    /// The path itself is malformed, e.g. an empty segment or a bad `[n]`.
+    InvalidPath,
+    /// The setting exists but holds a different type of value.
+    WrongType {
+        expected: &'static str,
+        actual: &'static str,
+    },
+    /// The setting is a number that does not fit in the requested type, or a
+    /// string that is not a single `char`.
+    OutOfRange {
+        expected: &'static str,
+        value: String,
+    },
+}
+
+impl fmt::Display for LookupErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LookupErrorKind::MissingKey => write!(f, "no such setting"),
+            LookupErrorKind::IndexOutOfBounds { index, len } => {
+                write!(f, "index {index} out of bounds, length is {len}")
+            }
+            LookupErrorKind::NotAGroup => write!(f, "parent is not a group"),
+            LookupErrorKind::NotAggregate => write!(f, "parent is not a group, array or list"),
+            LookupErrorKind::InvalidPath => write!(f, "invalid path"),
+            LookupErrorKind::WrongType { expected, actual } => {
+                write!(f, "expected {expected}, found {actual}")
+            }
+            LookupErrorKind::OutOfRange { expected, value } => {
+                write!(f, "{value} is out of range for {expected}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LookupErrorKind {}
+
+/// An error from looking up a setting by path, naming the segment of the path
+/// that failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LookupError {
+    path: String,
+    start: usize,
+    end: usize,
+    kind: LookupErrorKind,
+}
+
+impl LookupError {
+    /// An error at the segment of `path` spanning bytes `start..end`.
+    pub(crate) fn new(path: &str, start: usize, end: usize, kind: LookupErrorKind) -> Self {
+        LookupError {
+            path: path.into(),
+            start,
+            end,
+            kind,
+        }
+    }
+
+    /// The whole path that was looked up.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The segment of the path that failed, e.g. `scenes` or `[3]`.
+    pub fn segment(&self) -> &str {
+        &self.path[self.start..self.end]
+    }
+
+    /// The part of the path up to and including the failed segment.
+    pub fn failed_path(&self) -> &str {
+        &self.path[..self.end]
+    }
+
+    pub fn kind(&self) -> &LookupErrorKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for LookupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "lookup of \"{}\" failed at \"{}\": {}",
+            self.path,
+            self.segment(),
+            self.kind
+        )
+    }
+}
+
+impl std::error::Error for LookupError {}
+
+/// A way in which a [`Value`](crate::Value) breaks libconfig's type rules.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ViolationKind {
+    /// An array element has a different type than the first element.
+    MixedArray {
+        expected: &'static str,
+        actual: &'static str,
+    },
+    /// An array holds a group, array or list; only lists may.
+    NonScalarInArray { actual: &'static str },
+    /// A setting name is not of the form `[A-Za-z*][-A-Za-z0-9_*]*`.
+    InvalidName,
+    /// An integer is outside the 64-bit signed range libconfig can hold.
+    OutOfRange { value: String },
+}
+
+impl fmt::Display for ViolationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ViolationKind::MixedArray { expected, actual } => {
+                write!(f, "array of {expected} holds a {actual}")
+            }
+            ViolationKind::NonScalarInArray { actual } => {
+                write!(f, "array holds a {actual}, only lists may")
+            }
+            ViolationKind::InvalidName => write!(f, "invalid setting name"),
+            ViolationKind::OutOfRange { value } => {
+                write!(f, "{value} is out of range for int64")
+            }
+        }
+    }
+}
+
+/// A violation of libconfig's type rules found by
+/// [`Value::validate`](crate::Value::validate), with the path of the offending
+/// setting.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+    path: String,
+    kind: ViolationKind,
+}
+
+impl Violation {
+    pub(crate) fn new(path: String, kind: ViolationKind) -> Self {
+        Violation { path, kind }
+    }
+
+    /// The path of the offending setting, e.g. `window.sizes[2]`. Empty for
+    /// the root value.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn kind(&self) -> &ViolationKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\": {}", self.path, self.kind)
+    }
+}
+
+impl std::error::Error for Violation {}
@@ -0,0 +1,101 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Resolves the target of an `@include` directive, the equivalent of a
+/// libconfig include function set with `config_set_include_func`.
+pub trait IncludeResolver: Send + Sync {
+    /// Expands the path given to `@include` into the files it names, in the
+    /// order they should be spliced in. `include_dir` is the configured
+    /// include directory, if any. A pattern that matches nothing may return
+    /// an empty list.
+    fn resolve(&self, include_dir: Option<&Path>, path: &str) -> Result<Vec<PathBuf>, String>;
+
+    /// Reads the contents of a file returned by [`IncludeResolver::resolve`].
+    fn read(&self, path: &Path) -> Result<String, String>;
+}
+
+/// The default resolver, reading included files from disk.
+///
+/// Relative paths are resolved against the include directory, or the current
+/// directory when none is set. `*` and `?` wildcards in the file name expand
+/// to every matching file in that directory, in sorted order.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FileResolver;
+
+impl IncludeResolver for FileResolver {
+    fn resolve(&self, include_dir: Option<&Path>, path: &str) -> Result<Vec<PathBuf>, String> {
+        let path = match include_dir {
+            Some(dir) if Path::new(path).is_relative() => dir.join(path),
+            _ => PathBuf::from(path),
+        };
+
+        let Some(pattern) = path.file_name().and_then(|name| name.to_str()) else {
+            return Ok(vec![path]);
+        };
+
+        if !pattern.contains(['*', '?']) {
+            return Ok(vec![path]);
+        }
+
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+
+        let entries = fs::read_dir(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+
+        let mut files = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("{}: {e}", dir.display()))?;
+            let name = entry.file_name();
+            let matches = name
+                .to_str()
+                .is_some_and(|name| wildcard_match(pattern, name));
+            if matches && entry.path().is_file() {
+                files.push(dir.join(name));
+            }
+        }
+        files.sort();
+
+        Ok(files)
+    }
+
+    fn read(&self, path: &Path) -> Result<String, String> {
+        fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))
+    }
+}
+
+/// Match `name` against a pattern where `*` matches any run of characters and
+/// `?` matches exactly one.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
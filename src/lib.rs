@@ -1,8 +1,27 @@
+mod convert;
+mod error;
+mod include;
+mod macros;
+mod options;
 mod parser;
+mod path;
 mod printer;
 mod serde;
 mod value;
 
+pub use convert::*;
+pub use error::*;
+pub use include::*;
+pub use options::*;
 pub use serde::deserialize::*;
+pub use serde::error::{Error, ErrorKind};
+pub use serde::hex::*;
 pub use serde::serialize::*;
+pub use serde::stream::*;
+pub use serde::to_value::*;
 pub use value::*;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use indexmap::IndexMap;
+}
@@ -0,0 +1,114 @@
+/// Builds a [`Value`](crate::Value) from libconfig-like syntax.
+///
+/// `{ ... }` is a group of `name: value` (or `name = value`) settings, `[ ... ]`
+/// an array and `( ... )` a list. Anything else is a Rust expression,
+/// converted with `Value::from`, so an expression starting with `{`, `[` or
+/// `(` has to be bound to a variable first. Setting names that are not Rust
+/// identifiers can be written as string literals.
+///
+/// Settings end with `;` or `,`, which may be left out after the last
+/// setting and after a group, array or list.
+///
+/// ```
+/// use libconfig_rs::libconfig;
+///
+/// let width = 800;
+/// let value = libconfig!({
+///     name: "demo";
+///     window: { width: width; height: width * 3 / 4; }
+///     ids: [1, 2, 3];
+///     "mixed-list": ( "x", { enabled: true; } );
+/// });
+/// assert_eq!(value.lookup_value::<i32>("window.height"), Ok(600));
+/// ```
+#[macro_export]
+macro_rules! libconfig {
+    // The settings of a group, one at a time.
+    (@group $o:ident) => {};
+    (@group $o:ident ; $($rest:tt)*) => {
+        $crate::libconfig!(@group $o $($rest)*)
+    };
+    (@group $o:ident , $($rest:tt)*) => {
+        $crate::libconfig!(@group $o $($rest)*)
+    };
+    (@group $o:ident $key:ident : $($rest:tt)*) => {
+        $crate::libconfig!(@setting $o (stringify!($key)) [] $($rest)*)
+    };
+    (@group $o:ident $key:ident = $($rest:tt)*) => {
+        $crate::libconfig!(@setting $o (stringify!($key)) [] $($rest)*)
+    };
+    (@group $o:ident $key:literal : $($rest:tt)*) => {
+        $crate::libconfig!(@setting $o ($key) [] $($rest)*)
+    };
+    (@group $o:ident $key:literal = $($rest:tt)*) => {
+        $crate::libconfig!(@setting $o ($key) [] $($rest)*)
+    };
+
+    // A setting's value: a group, array or list, or the tokens of an
+    // expression up to the terminator.
+    (@setting $o:ident ($key:expr) [] { $($value:tt)* } $($rest:tt)*) => {
+        $crate::libconfig!(@insert $o ($key) { $($value)* });
+        $crate::libconfig!(@group $o $($rest)*);
+    };
+    (@setting $o:ident ($key:expr) [] [ $($value:tt)* ] $($rest:tt)*) => {
+        $crate::libconfig!(@insert $o ($key) [ $($value)* ]);
+        $crate::libconfig!(@group $o $($rest)*);
+    };
+    (@setting $o:ident ($key:expr) [] ( $($value:tt)* ) $($rest:tt)*) => {
+        $crate::libconfig!(@insert $o ($key) ( $($value)* ));
+        $crate::libconfig!(@group $o $($rest)*);
+    };
+    (@setting $o:ident ($key:expr) [$($value:tt)+] ; $($rest:tt)*) => {
+        $crate::libconfig!(@insert $o ($key) $($value)+);
+        $crate::libconfig!(@group $o $($rest)*);
+    };
+    (@setting $o:ident ($key:expr) [$($value:tt)+] , $($rest:tt)*) => {
+        $crate::libconfig!(@insert $o ($key) $($value)+);
+        $crate::libconfig!(@group $o $($rest)*);
+    };
+    (@setting $o:ident ($key:expr) [$($value:tt)+]) => {
+        $crate::libconfig!(@insert $o ($key) $($value)+);
+    };
+    (@setting $o:ident ($key:expr) [$($value:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::libconfig!(@setting $o ($key) [$($value)* $next] $($rest)*)
+    };
+
+    (@insert $o:ident ($key:expr) $($value:tt)+) => {
+        $o.insert(::std::string::String::from($key), $crate::libconfig!($($value)+));
+    };
+
+    // The elements of an array or list, separated by commas.
+    (@elements $v:ident []) => {};
+    (@elements $v:ident [$($element:tt)+]) => {
+        $v.push($crate::libconfig!($($element)+));
+    };
+    (@elements $v:ident [$($element:tt)+] , $($rest:tt)*) => {
+        $v.push($crate::libconfig!($($element)+));
+        $crate::libconfig!(@elements $v [] $($rest)*);
+    };
+    (@elements $v:ident [$($element:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::libconfig!(@elements $v [$($element)* $next] $($rest)*)
+    };
+
+    ({ $($settings:tt)* }) => {{
+        #[allow(unused_mut)]
+        let mut settings = $crate::__private::IndexMap::new();
+        $crate::libconfig!(@group settings $($settings)*);
+        $crate::Value::Object(settings)
+    }};
+    ([ $($elements:tt)* ]) => {{
+        #[allow(unused_mut)]
+        let mut elements = ::std::vec::Vec::<$crate::Value>::new();
+        $crate::libconfig!(@elements elements [] $($elements)*);
+        $crate::Value::Array(elements, $crate::ArrayType::Array)
+    }};
+    (( $($elements:tt)* )) => {{
+        #[allow(unused_mut)]
+        let mut elements = ::std::vec::Vec::<$crate::Value>::new();
+        $crate::libconfig!(@elements elements [] $($elements)*);
+        $crate::Value::Array(elements, $crate::ArrayType::List)
+    }};
+    ($value:expr) => {
+        $crate::Value::from($value)
+    };
+}
@@ -0,0 +1,301 @@
+use crate::{FileResolver, IncludeResolver};
+use std::{fmt, path::PathBuf, sync::Arc};
+
+/// What to do with an integer literal that does not fit in 64 bits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IntegerOverflow {
+    /// Fail with a parse error at the literal.
+    #[default]
+    Error,
+    /// Keep literals up to `u64::MAX` as a `Value::UInt64`. Anything larger,
+    /// or negative, is still an error.
+    U64,
+    /// Read the literal as the nearest `Value::Float`, losing precision.
+    Float,
+}
+
+/// What to do when a group sets the same name more than once.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicateSettings {
+    /// Fail with a parse error at the second setting, like libconfig.
+    #[default]
+    Error,
+    /// Keep the last value, in the position of the first setting, like
+    /// libconfig's `CONFIG_OPTION_ALLOW_OVERRIDES`.
+    LastWins,
+    /// Keep the first value and ignore later ones.
+    FirstWins,
+}
+
+/// Settings that control how libconfig text is read.
+#[derive(Clone, Default)]
+pub struct ParseOptions {
+    pub(crate) include_dir: Option<PathBuf>,
+    pub(crate) include_resolver: Option<Arc<dyn IncludeResolver>>,
+    pub(crate) integer_overflow: IntegerOverflow,
+    pub(crate) duplicate_settings: DuplicateSettings,
+    pub(crate) autoconvert: bool,
+    pub(crate) case_sensitive_bools: bool,
+    pub(crate) strict_terminators: bool,
+}
+
+impl ParseOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Directory that relative `@include` paths are resolved against, like
+    /// `config_set_include_dir`.
+    pub fn include_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.include_dir = Some(dir.into());
+        self
+    }
+
+    /// Use `resolver` instead of the file system to find and read included
+    /// files, like `config_set_include_func`.
+    pub fn include_resolver(mut self, resolver: impl IncludeResolver + 'static) -> Self {
+        self.include_resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    /// How to read integer literals too large for 64 bits. By default they
+    /// are an error rather than silently losing precision.
+    pub fn integer_overflow(mut self, policy: IntegerOverflow) -> Self {
+        self.integer_overflow = policy;
+        self
+    }
+
+    /// What to do when a group sets the same name twice, including through
+    /// an `@include`. By default it is an error.
+    pub fn duplicate_settings(mut self, policy: DuplicateSettings) -> Self {
+        self.duplicate_settings = policy;
+        self
+    }
+
+    /// Shorthand for [`ParseOptions::duplicate_settings`]: `true` keeps the
+    /// last of several settings with the same name, like libconfig's
+    /// `CONFIG_OPTION_ALLOW_OVERRIDES`, and `false` makes them an error.
+    pub fn allow_overrides(self, allow: bool) -> Self {
+        self.duplicate_settings(if allow {
+            DuplicateSettings::LastWins
+        } else {
+            DuplicateSettings::Error
+        })
+    }
+
+    /// Let [`from_str_with_options`](crate::from_str_with_options) convert
+    /// between integers and floats where the target type asks for the other,
+    /// like libconfig's `CONFIG_OPTION_AUTOCONVERT`. Floats are truncated.
+    pub fn autoconvert(mut self, autoconvert: bool) -> Self {
+        self.autoconvert = autoconvert;
+        self
+    }
+
+    /// Only accept `true` and `false` in lower case. libconfig, and this
+    /// crate by default, also accept `TRUE`, `False` and so on.
+    pub fn case_sensitive_bools(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive_bools = case_sensitive;
+        self
+    }
+
+    /// Require a `;` or `,` after every setting, including the last one in a
+    /// group. By default they may be left out.
+    pub fn strict_terminators(mut self, strict: bool) -> Self {
+        self.strict_terminators = strict;
+        self
+    }
+
+    pub(crate) fn resolver(&self) -> &dyn IncludeResolver {
+        match &self.include_resolver {
+            Some(resolver) => resolver.as_ref(),
+            None => &FileResolver,
+        }
+    }
+}
+
+impl fmt::Debug for ParseOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParseOptions")
+            .field("include_dir", &self.include_dir)
+            .field("include_resolver", &self.include_resolver.is_some())
+            .field("integer_overflow", &self.integer_overflow)
+            .field("duplicate_settings", &self.duplicate_settings)
+            .field("autoconvert", &self.autoconvert)
+            .field("case_sensitive_bools", &self.case_sensitive_bools)
+            .field("strict_terminators", &self.strict_terminators)
+            .finish()
+    }
+}
+
+/// Which escape sequences are used when writing strings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EscapeStyle {
+    /// Rust's escapes, such as `\u{1b}` and `\0`. This crate reads them back,
+    /// but the C libconfig does not.
+    Rust,
+    /// Only the escapes the C libconfig reads: `\\`, `\"`, `\n`, `\r`, `\t`,
+    /// `\f` and `\xNN` for other control characters. Everything else,
+    /// including non-ASCII text, is written as UTF-8.
+    #[default]
+    Libconfig,
+}
+
+/// What is written after each setting. libconfig reads all three.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Terminator {
+    /// `a : 1;`, like libconfig's `CONFIG_OPTION_SEMICOLON_SEPARATORS`.
+    #[default]
+    Semicolon,
+    /// `a : 1,`
+    Comma,
+    /// `a : 1`, relying on the line break between settings.
+    Omit,
+}
+
+impl Terminator {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Terminator::Semicolon => ";",
+            Terminator::Comma => ",",
+            Terminator::Omit => "",
+        }
+    }
+}
+
+/// What each level of nesting is indented with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Indent {
+    /// This many spaces per level; 4 by default.
+    Spaces(usize),
+    /// One tab per level.
+    Tabs,
+}
+
+impl Default for Indent {
+    fn default() -> Self {
+        Indent::Spaces(4)
+    }
+}
+
+/// What separates a setting's name from its value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Assignment {
+    /// `a : 1;`
+    #[default]
+    Colon,
+    /// `a = 1;`, as the C libconfig writes it.
+    Equals,
+}
+
+/// Settings that control how libconfig text is written, by
+/// [`Value::to_document_string_with_options`](crate::Value::to_document_string_with_options)
+/// and [`to_string_with_options`](crate::to_string_with_options).
+#[derive(Clone, Debug)]
+pub struct FormatOptions {
+    pub(crate) string_width: Option<usize>,
+    pub(crate) max_width: Option<usize>,
+    pub(crate) escapes: EscapeStyle,
+    pub(crate) terminator: Terminator,
+    pub(crate) indent: Indent,
+    pub(crate) assignment: Assignment,
+    pub(crate) colon_for_groups: bool,
+    pub(crate) brace_on_own_line: bool,
+    pub(crate) float_precision: Option<usize>,
+    pub(crate) trailing_newline: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            string_width: None,
+            max_width: None,
+            escapes: EscapeStyle::default(),
+            terminator: Terminator::default(),
+            indent: Indent::default(),
+            assignment: Assignment::default(),
+            colon_for_groups: false,
+            brace_on_own_line: false,
+            float_precision: None,
+            trailing_newline: true,
+        }
+    }
+}
+
+impl FormatOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// What to indent each level of nesting with.
+    pub fn indent(mut self, indent: Indent) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// What to write between a setting's name and its value.
+    pub fn assignment(mut self, assignment: Assignment) -> Self {
+        self.assignment = assignment;
+        self
+    }
+
+    /// Write `:` rather than `=` before groups, like libconfig's
+    /// `CONFIG_OPTION_COLON_ASSIGNMENT_FOR_GROUPS`. Only has an effect with
+    /// [`Assignment::Equals`].
+    pub fn colon_for_groups(mut self, colon: bool) -> Self {
+        self.colon_for_groups = colon;
+        self
+    }
+
+    /// Put the `{` opening a group on its own line, like libconfig's
+    /// `CONFIG_OPTION_OPEN_BRACE_ON_SEPARATE_LINE`.
+    pub fn brace_on_own_line(mut self, own_line: bool) -> Self {
+        self.brace_on_own_line = own_line;
+        self
+    }
+
+    /// Write floats with this many digits after the decimal point, like
+    /// `config_set_float_precision`. Magnitudes below 1e-4 or from 1e10 up
+    /// are written with an exponent, e.g. `1.00e300`. By default floats are
+    /// written with as many digits as it takes to read back the same value.
+    pub fn float_precision(mut self, digits: usize) -> Self {
+        self.float_precision = Some(digits);
+        self
+    }
+
+    /// End the output with a line break after the last setting. On by
+    /// default.
+    pub fn trailing_newline(mut self, newline: bool) -> Self {
+        self.trailing_newline = newline;
+        self
+    }
+
+    /// Split strings longer than `width` characters into adjacent literals,
+    /// one per line, preferring to break after a space. Reading the output
+    /// joins them back together.
+    pub fn split_strings(mut self, width: usize) -> Self {
+        self.string_width = Some(width.max(1));
+        self
+    }
+
+    /// Write arrays and lists that would run past column `width` with one
+    /// element per line, indented one level in from the setting. Shorter ones,
+    /// and all of them when this is not set, stay on one line. A list holding
+    /// a group is always broken up. Tabs count as 8 columns.
+    pub fn max_width(mut self, width: usize) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+
+    /// Which escape sequences to use in strings. The default,
+    /// [`EscapeStyle::Libconfig`], writes files the C library can read.
+    pub fn escapes(mut self, style: EscapeStyle) -> Self {
+        self.escapes = style;
+        self
+    }
+
+    /// What to write after each setting.
+    pub fn terminator(mut self, terminator: Terminator) -> Self {
+        self.terminator = terminator;
+        self
+    }
+}
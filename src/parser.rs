@@ -1,48 +1,346 @@
-use crate::{ArrayType, Value};
+use crate::{
+    ArrayType, DuplicateSettings, IntFormat, IntegerOverflow, ParseOptions, Value, path::Segment,
+};
 use indexmap::IndexMap;
 use nom::{
-    IResult, Parser,
+    Finish, IResult, Offset, Parser,
     branch::alt,
-    bytes::complete::{tag, tag_no_case, take_while},
-    character::complete::{alpha1, alphanumeric1, char, one_of},
-    combinator::{cut, map, map_res, opt, recognize, value},
-    error::{ContextError, FromExternalError, ParseError, context},
-    multi::{many0_count, many1, separated_list0},
+    bytes::complete::{tag, tag_no_case, take_till, take_while1},
+    character::complete::{alpha1, alphanumeric1, char, digit1, multispace1, one_of},
+    combinator::{cut, eof, map, not, opt, recognize, value},
+    error::{ContextError, ErrorKind, FromExternalError, ParseError, context},
+    multi::{many0, many0_count, separated_list1},
     number::complete::double,
-    sequence::{delimited, pair, preceded, separated_pair, terminated},
+    sequence::{delimited, pair, preceded, terminated},
+};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    fmt::Write,
+    path::PathBuf,
 };
+
+mod error;
+mod reader;
 mod string;
 
-fn sp<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, &'a str, E> {
-    let chars = " \t\r\n";
-    take_while(move |c| chars.contains(c)).parse(i)
+pub use error::{Error, Invalid};
+pub use reader::{Kind, Reader, Scalar, Setting};
+
+/// Maximum nesting of `@include` directives, as in libconfig.
+const MAX_INCLUDE_DEPTH: usize = 10;
+
+/// State shared by the parser functions while reading one document.
+pub struct Context {
+    options: ParseOptions,
+    /// Files currently being parsed, outermost first.
+    files: RefCell<Vec<PathBuf>>,
+    /// Where values start, when asked for with [`Context::recording_positions`].
+    positions: Option<RefCell<Positions>>,
 }
 
-fn boolean<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, bool, E> {
-    alt((
-        value(true, tag_no_case("true")),
-        value(false, tag_no_case("false")),
-    ))
-    .parse(input)
+/// The path of the value being parsed, and where each value parsed so far
+/// starts, as the length of the input remaining there.
+struct Positions {
+    path: String,
+    starts: HashMap<String, usize>,
+    /// How many files deep the recorded input is; values from files it
+    /// includes are not recorded.
+    depth: usize,
 }
 
-fn number<'a, E: ParseError<&'a str> + FromExternalError<&'a str, std::num::ParseIntError>>(
+impl Context {
+    pub fn new(options: &ParseOptions) -> Self {
+        Context {
+            options: options.clone(),
+            files: RefCell::new(Vec::new()),
+            positions: None,
+        }
+    }
+
+    /// Like [`Context::new`], for parsing the contents of `file`.
+    pub fn for_file(options: &ParseOptions, file: PathBuf) -> Self {
+        let ctx = Self::new(options);
+        ctx.files.borrow_mut().push(file);
+        ctx
+    }
+
+    /// Also record where each value starts, by its path in the form
+    /// `a.b[0].c`. The root value has the empty path.
+    pub fn recording_positions(mut self) -> Self {
+        self.positions = Some(RefCell::new(Positions {
+            path: String::new(),
+            starts: HashMap::new(),
+            depth: self.files.borrow().len(),
+        }));
+        self
+    }
+
+    /// The recorded value starts, as the length of the input remaining at
+    /// each.
+    pub fn into_positions(self) -> HashMap<String, usize> {
+        self.positions
+            .map(|p| p.into_inner().starts)
+            .unwrap_or_default()
+    }
+
+    /// Extend the path of the value being parsed, returning the length to
+    /// restore with [`Context::leave`].
+    fn enter(&self, segment: Segment) -> usize {
+        let Some(positions) = &self.positions else {
+            return 0;
+        };
+        let path = &mut positions.borrow_mut().path;
+        let len = path.len();
+        match segment {
+            Segment::Key(key) => {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+            }
+            Segment::Index(index) => write!(path, "[{index}]").unwrap(),
+        }
+        len
+    }
+
+    fn leave(&self, len: usize) {
+        if let Some(positions) = &self.positions {
+            positions.borrow_mut().path.truncate(len);
+        }
+    }
+
+    /// Note that the value at the current path starts at `i`. Of duplicate
+    /// settings, the position of the one that is kept is recorded.
+    fn record(&self, i: &str) {
+        let Some(positions) = &self.positions else {
+            return;
+        };
+        let positions = &mut *positions.borrow_mut();
+        if self.files.borrow().len() != positions.depth {
+            return;
+        }
+        let path = positions.path.clone();
+        match self.options.duplicate_settings {
+            DuplicateSettings::FirstWins => {
+                positions.starts.entry(path).or_insert(i.len());
+            }
+            _ => {
+                positions.starts.insert(path, i.len());
+            }
+        }
+    }
+
+    /// Add a setting under the duplicate policy. Returns `false` if the name
+    /// is already set and duplicates are an error.
+    fn insert(&self, settings: &mut IndexMap<String, Value>, key: String, value: Value) -> bool {
+        match (settings.get_mut(&key), self.options.duplicate_settings) {
+            (None, _) => {
+                settings.insert(key, value);
+                true
+            }
+            (Some(_), DuplicateSettings::Error) => false,
+            (Some(existing), DuplicateSettings::LastWins) => {
+                *existing = value;
+                true
+            }
+            (Some(_), DuplicateSettings::FirstWins) => true,
+        }
+    }
+
+    /// Resolve an `@include` path and parse the settings of every file it names.
+    fn include(&self, path: &str) -> Result<IndexMap<String, Value>, Invalid> {
+        let resolver = self.options.resolver();
+        let files = resolver
+            .resolve(self.options.include_dir.as_deref(), path)
+            .map_err(Invalid::Include)?;
+
+        let mut settings = IndexMap::new();
+        for file in files {
+            if self.files.borrow().contains(&file) {
+                let chain = self
+                    .files
+                    .borrow()
+                    .iter()
+                    .chain([&file])
+                    .map(|f| f.display().to_string())
+                    .collect::<Vec<_>>();
+                return Err(Invalid::Include(format!(
+                    "include cycle: {}",
+                    chain.join(" -> ")
+                )));
+            }
+            if self.files.borrow().len() >= MAX_INCLUDE_DEPTH {
+                return Err(Invalid::Include(format!(
+                    "{}: includes nested more than {MAX_INCLUDE_DEPTH} levels deep",
+                    file.display()
+                )));
+            }
+
+            let input = resolver.read(&file).map_err(Invalid::Include)?;
+
+            self.files.borrow_mut().push(file.clone());
+            let res = document::<Error>(self, &input)
+                .finish()
+                .map(|(_, included)| included)
+                .map_err(|e| e.into_parse_error(&input).with_file(&file));
+            self.files.borrow_mut().pop();
+
+            for (key, value) in res.map_err(|e| Invalid::IncludedFile(Box::new(e)))? {
+                if !self.insert(&mut settings, key.clone(), value) {
+                    return Err(Invalid::Duplicate { key, first: None });
+                }
+            }
+        }
+
+        Ok(settings)
+    }
+}
+
+/// Parse a `#` or `//` comment running to the end of the line, or a
+/// `/* ... */` block comment.
+fn comment<'a, E: ParseError<&'a str> + FromExternalError<&'a str, Invalid>>(
     i: &'a str,
-) -> IResult<&'a str, i64, E> {
-    terminated(
+) -> IResult<&'a str, &'a str, E> {
+    if let Some(rest) = i.strip_prefix("/*") {
+        return match rest.find("*/") {
+            Some(end) => Ok((&rest[end + 2..], &i[..end + 4])),
+            None => Err(nom::Err::Failure(E::from_external_error(
+                i,
+                ErrorKind::TakeUntil,
+                Invalid::UnterminatedComment,
+            ))),
+        };
+    }
+
+    recognize(pair(alt((tag("#"), tag("//"))), take_till(|c| c == '\n'))).parse(i)
+}
+
+/// Skip any amount of whitespace and comments.
+fn sp<'a, E: ParseError<&'a str> + FromExternalError<&'a str, Invalid>>(
+    i: &'a str,
+) -> IResult<&'a str, &'a str, E> {
+    recognize(many0_count(alt((multispace1, comment)))).parse(i)
+}
+
+/// Names what `parser` reads when it fails without committing, like
+/// `context` but without adding to the context stack of errors from inside it.
+fn expected<'a, O, E: ParseError<&'a str> + ContextError<&'a str>>(
+    label: &'static str,
+    mut parser: impl Parser<&'a str, Output = O, Error = E>,
+) -> impl Parser<&'a str, Output = O, Error = E> {
+    move |i: &'a str| match parser.parse(i) {
+        Err(nom::Err::Error(e)) => Err(nom::Err::Error(E::add_context(i, label, e))),
+        res => res,
+    }
+}
+
+fn boolean<'a, E: ParseError<&'a str>>(ctx: &Context, input: &'a str) -> IResult<&'a str, bool, E> {
+    if ctx.options.case_sensitive_bools {
+        alt((value(true, tag("true")), value(false, tag("false")))).parse(input)
+    } else {
         alt((
-            map_res(
-                recognize(many1(one_of("0123456789.eE"))),
-                |digit_str: &str| digit_str.parse::<i64>(),
-            ),
-            map_res(
-                preceded(tag("-"), recognize(many1(one_of("0123456789.eE")))),
-                |digit_str: &str| digit_str.parse::<i64>().map(|v| -v),
-            ),
+            value(true, tag_no_case("true")),
+            value(false, tag_no_case("false")),
+        ))
+        .parse(input)
+    }
+}
+
+/// Parse the digits of an integer literal, after an optional radix prefix.
+fn digits<'a, E: ParseError<&'a str>>(
+    prefix: &'static str,
+    format: IntFormat,
+) -> impl Parser<&'a str, Output = (&'a str, IntFormat), Error = E> {
+    let radix = format.radix();
+    map(
+        preceded(
+            tag_no_case(prefix),
+            take_while1(move |c: char| c.is_digit(radix)),
+        ),
+        move |digits| (digits, format),
+    )
+}
+
+/// Parse an integer literal. It is an `int64` when it has an `L` or `LL`
+/// suffix or does not fit in 32 bits. Literals too large for 64 bits are
+/// handled according to [`ParseOptions::integer_overflow`].
+///
+/// As in libconfig, hex, binary and octal literals have no sign and are read
+/// as two's complement: `0xFFFFFFFF` is the `int` -1, and
+/// `0xFFFFFFFFFFFFFFFFL` the `int64` -1.
+fn number<'a, E: ParseError<&'a str> + FromExternalError<&'a str, Invalid>>(
+    ctx: &Context,
+    i: &'a str,
+) -> IResult<&'a str, Value, E> {
+    let (rest, (sign, (digits, format), suffix)) = (
+        opt(one_of("+-")),
+        alt((
+            digits("0x", IntFormat::Hex),
+            digits("0b", IntFormat::Binary),
+            digits("0o", IntFormat::Octal),
+            map(terminated(digit1, not(one_of(".eE"))), |digits| {
+                (digits, IntFormat::Decimal)
+            }),
         )),
-        opt(tag("L")),
+        opt(alt((tag("LL"), tag("L")))),
     )
-    .parse(i)
+        .parse(i)?;
+
+    if sign.is_some() && format != IntFormat::Decimal {
+        return Err(nom::Err::Failure(E::from_external_error(
+            i,
+            ErrorKind::Verify,
+            Invalid::SignedRadix,
+        )));
+    }
+
+    let negative = sign == Some('-');
+    let magnitude = u64::from_str_radix(digits, format.radix()).ok();
+    let value = match (format, magnitude) {
+        (IntFormat::Decimal, Some(m)) if negative => 0i64.checked_sub_unsigned(m),
+        (IntFormat::Decimal, Some(m)) => i64::try_from(m).ok(),
+        (_, Some(m)) => {
+            let value = match u32::try_from(m) {
+                Ok(m) if suffix.is_none() => Value::Int(m as i32, format),
+                _ => Value::Int64(m as i64, format),
+            };
+            return Ok((rest, value));
+        }
+        (_, None) => None,
+    };
+
+    let value = match value {
+        Some(v) => match i32::try_from(v) {
+            Ok(v) if suffix.is_none() => Value::Int(v, format),
+            _ => Value::Int64(v, format),
+        },
+        None => match (ctx.options.integer_overflow, negative, magnitude) {
+            (IntegerOverflow::U64, false, Some(m)) => Value::UInt64(m, format),
+            (IntegerOverflow::Float, ..) => {
+                let f = int_to_float(digits, format);
+                Value::Float(if negative { -f } else { f })
+            }
+            _ => {
+                return Err(nom::Err::Failure(E::from_external_error(
+                    i,
+                    ErrorKind::MapRes,
+                    Invalid::IntegerOverflow,
+                )));
+            }
+        },
+    };
+    Ok((rest, value))
+}
+
+/// The nearest float to the unsigned integer written as `digits`.
+fn int_to_float(digits: &str, format: IntFormat) -> f64 {
+    match format {
+        IntFormat::Decimal => digits.parse().unwrap_or(f64::INFINITY),
+        _ => digits.chars().fold(0.0, |acc, c| {
+            acc * f64::from(format.radix()) + f64::from(c.to_digit(format.radix()).unwrap_or(0))
+        }),
+    }
 }
 
 fn key<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
@@ -58,33 +356,71 @@ fn key<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
     .parse(i)
 }
 
+/// Parse a string, joining adjacent literals like `"abc" "def"`, which may be
+/// separated by whitespace and comments.
 fn string<
     'a,
     E: ParseError<&'a str>
         + ContextError<&'a str>
-        + FromExternalError<&'a str, std::num::ParseIntError>,
+        + FromExternalError<&'a str, std::num::ParseIntError>
+        + FromExternalError<&'a str, Invalid>,
 >(
     i: &'a str,
 ) -> IResult<&'a str, String, E> {
-    context("string", string::parse).parse(i)
+    context(
+        "string",
+        map(
+            pair(string::parse, many0(preceded(sp, string::parse))),
+            |(first, rest)| rest.iter().fold(first, |acc, s| acc + s),
+        ),
+    )
+    .parse(i)
+}
+
+/// Parse the comma-separated values of an array or list, allowing a trailing
+/// comma after the last one.
+fn elements<
+    'a,
+    E: ParseError<&'a str>
+        + ContextError<&'a str>
+        + FromExternalError<&'a str, std::num::ParseIntError>
+        + FromExternalError<&'a str, Invalid>,
+>(
+    ctx: &Context,
+    i: &'a str,
+) -> IResult<&'a str, Vec<Value>, E> {
+    let index = Cell::new(0);
+    let element = |i| {
+        let len = ctx.enter(Segment::Index(index.replace(index.get() + 1)));
+        let res = libconfig_value(ctx, i);
+        ctx.leave(len);
+        res
+    };
+    map(
+        opt(terminated(
+            separated_list1(preceded(sp, char(',')), element),
+            opt(preceded(sp, char(','))),
+        )),
+        Option::unwrap_or_default,
+    )
+    .parse(i)
 }
 
 fn array<
     'a,
     E: ParseError<&'a str>
         + ContextError<&'a str>
-        + FromExternalError<&'a str, std::num::ParseIntError>,
+        + FromExternalError<&'a str, std::num::ParseIntError>
+        + FromExternalError<&'a str, Invalid>,
 >(
+    ctx: &Context,
     i: &'a str,
 ) -> IResult<&'a str, Vec<Value>, E> {
     context(
         "array",
         preceded(
             char('['),
-            cut(terminated(
-                separated_list0(preceded(sp, char(',')), libconfig_value),
-                preceded(sp, char(']')),
-            )),
+            cut(terminated(|i| elements(ctx, i), preceded(sp, char(']')))),
         ),
     )
     .parse(i)
@@ -94,18 +430,17 @@ fn list<
     'a,
     E: ParseError<&'a str>
         + ContextError<&'a str>
-        + FromExternalError<&'a str, std::num::ParseIntError>,
+        + FromExternalError<&'a str, std::num::ParseIntError>
+        + FromExternalError<&'a str, Invalid>,
 >(
+    ctx: &Context,
     i: &'a str,
 ) -> IResult<&'a str, Vec<Value>, E> {
     context(
         "list",
         preceded(
             char('('),
-            cut(terminated(
-                separated_list0(preceded(sp, char(',')), libconfig_value),
-                preceded(sp, char(')')),
-            )),
+            cut(terminated(|i| elements(ctx, i), preceded(sp, char(')')))),
         ),
     )
     .parse(i)
@@ -115,42 +450,175 @@ fn key_value<
     'a,
     E: ParseError<&'a str>
         + ContextError<&'a str>
-        + FromExternalError<&'a str, std::num::ParseIntError>,
+        + FromExternalError<&'a str, std::num::ParseIntError>
+        + FromExternalError<&'a str, Invalid>,
 >(
+    ctx: &Context,
     i: &'a str,
 ) -> IResult<&'a str, (&'a str, Value), E> {
-    terminated(
-        separated_pair(
-            preceded(sp, key),
-            cut(preceded(sp, one_of("=:"))),
-            libconfig_value,
-        ),
-        tag(";"),
-    )
+    let (i, k) = preceded(sp, key).parse(i)?;
+    let len = ctx.enter(Segment::Key(k));
+    let res = setting_value(ctx, i);
+    ctx.leave(len);
+    res.map(|(i, v)| (i, (k, v)))
+}
+
+/// Parse the `=` or `:` after a setting name, the value and its terminator.
+fn setting_value<
+    'a,
+    E: ParseError<&'a str>
+        + ContextError<&'a str>
+        + FromExternalError<&'a str, std::num::ParseIntError>
+        + FromExternalError<&'a str, Invalid>,
+>(
+    ctx: &Context,
+    i: &'a str,
+) -> IResult<&'a str, Value, E> {
+    cut(delimited(
+        assignment,
+        |i| libconfig_value(ctx, i),
+        |i| terminator(ctx, i),
+    ))
     .parse(i)
 }
 
+/// Parse the `=` or `:` between a setting name and its value.
+fn assignment<'a, E: ParseError<&'a str> + FromExternalError<&'a str, Invalid>>(
+    i: &'a str,
+) -> IResult<&'a str, char, E> {
+    preceded(sp, alt((char('='), char(':')))).parse(i)
+}
+
+/// Parse the `;` or `,` after a setting, which is optional unless
+/// terminators are strict.
+fn terminator<'a, E: ParseError<&'a str> + FromExternalError<&'a str, Invalid>>(
+    ctx: &Context,
+    i: &'a str,
+) -> IResult<&'a str, Option<char>, E> {
+    let terminator = preceded(sp, alt((char(';'), char(','))));
+    if ctx.options.strict_terminators {
+        map(terminator, Some).parse(i)
+    } else {
+        opt(terminator).parse(i)
+    }
+}
+
+fn include<
+    'a,
+    E: ParseError<&'a str>
+        + ContextError<&'a str>
+        + FromExternalError<&'a str, std::num::ParseIntError>
+        + FromExternalError<&'a str, Invalid>,
+>(
+    ctx: &Context,
+    i: &'a str,
+) -> IResult<&'a str, IndexMap<String, Value>, E> {
+    let (i, _) = sp(i)?;
+    let (rest, path) = preceded(
+        tag("@include"),
+        cut(context("include", preceded(sp, string))),
+    )
+    .parse(i)?;
+
+    match ctx.include(&path) {
+        Ok(settings) => Ok((rest, settings)),
+        // Settings that two of the included files both set are placed at the
+        // `@include` itself.
+        Err(Invalid::Duplicate { key, .. }) => Err(nom::Err::Failure(E::from_external_error(
+            i,
+            ErrorKind::Verify,
+            Invalid::Duplicate {
+                key,
+                first: Some(i.len()),
+            },
+        ))),
+        Err(e) => Err(nom::Err::Failure(E::from_external_error(
+            i,
+            ErrorKind::Fail,
+            e,
+        ))),
+    }
+}
+
+/// A single entry in a list of settings.
+enum Entry<'a> {
+    Setting(&'a str, Value),
+    Include(IndexMap<String, Value>),
+}
+
+fn settings<
+    'a,
+    E: ParseError<&'a str>
+        + ContextError<&'a str>
+        + FromExternalError<&'a str, std::num::ParseIntError>
+        + FromExternalError<&'a str, Invalid>,
+>(
+    ctx: &Context,
+    mut i: &'a str,
+) -> IResult<&'a str, IndexMap<String, Value>, E> {
+    let mut settings = IndexMap::new();
+    // Where each setting was set, as the input remaining from its name, or
+    // from the `@include` that read it.
+    let mut locations: Vec<usize> = Vec::new();
+
+    loop {
+        let entry = alt((
+            map(|i| key_value(ctx, i), |(k, v)| Entry::Setting(k, v)),
+            map(|i| include(ctx, i), Entry::Include),
+        ))
+        .parse(i);
+        let (rest, entry) = match entry {
+            Ok(res) => res,
+            Err(nom::Err::Error(_)) => return Ok((i, settings)),
+            Err(e) => return Err(e),
+        };
+
+        let (at, location, entries) = match entry {
+            Entry::Setting(k, v) => {
+                let at = &i[i.offset(k)..];
+                (at, at.len(), vec![(String::from(k), v)])
+            }
+            Entry::Include(included) => {
+                let at = sp::<E>(i).map_or(i, |(at, _)| at);
+                (at, at.len(), included.into_iter().collect())
+            }
+        };
+        for (key, value) in entries {
+            if let Some(index) = settings.get_index_of(&key) {
+                if !ctx.insert(&mut settings, key.clone(), value) {
+                    return Err(nom::Err::Failure(E::from_external_error(
+                        at,
+                        ErrorKind::Verify,
+                        Invalid::Duplicate {
+                            key,
+                            first: Some(locations[index]),
+                        },
+                    )));
+                }
+            } else {
+                settings.insert(key, value);
+                locations.push(location);
+            }
+        }
+        i = rest;
+    }
+}
+
 fn hash<
     'a,
     E: ParseError<&'a str>
         + ContextError<&'a str>
-        + FromExternalError<&'a str, std::num::ParseIntError>,
+        + FromExternalError<&'a str, std::num::ParseIntError>
+        + FromExternalError<&'a str, Invalid>,
 >(
+    ctx: &Context,
     i: &'a str,
 ) -> IResult<&'a str, IndexMap<String, Value>, E> {
     context(
         "map",
         preceded(
             char('{'),
-            cut(terminated(
-                map(separated_list0(sp, key_value), |tuple_vec| {
-                    tuple_vec
-                        .into_iter()
-                        .map(|(k, v)| (String::from(k), v))
-                        .collect()
-                }),
-                preceded(sp, char('}')),
-            )),
+            cut(terminated(|i| settings(ctx, i), preceded(sp, char('}')))),
         ),
     )
     .parse(i)
@@ -160,32 +628,77 @@ fn libconfig_value<
     'a,
     E: ParseError<&'a str>
         + ContextError<&'a str>
-        + FromExternalError<&'a str, std::num::ParseIntError>,
+        + FromExternalError<&'a str, std::num::ParseIntError>
+        + FromExternalError<&'a str, Invalid>,
 >(
+    ctx: &Context,
     i: &'a str,
 ) -> IResult<&'a str, Value, E> {
-    preceded(
-        sp,
+    let (i, _) = sp(i)?;
+    ctx.record(i);
+    expected(
+        "value",
         alt((
-            map(hash, Value::Object),
-            map(array, |v| Value::Array(v, ArrayType::Array)),
-            map(list, |v| Value::Array(v, ArrayType::List)),
-            map(string, Value::String),
-            map(boolean, Value::Bool),
-            map(number, Value::Int),
-            map(double, Value::Float),
+            map(|i| hash(ctx, i), Value::Object),
+            map(|i| array(ctx, i), |v| Value::Array(v, ArrayType::Array)),
+            map(|i| list(ctx, i), |v| Value::Array(v, ArrayType::List)),
+            |i| scalar(ctx, i),
         )),
     )
     .parse(i)
 }
 
+/// Parse a value that is not a group, array or list.
+fn scalar<
+    'a,
+    E: ParseError<&'a str>
+        + ContextError<&'a str>
+        + FromExternalError<&'a str, std::num::ParseIntError>
+        + FromExternalError<&'a str, Invalid>,
+>(
+    ctx: &Context,
+    i: &'a str,
+) -> IResult<&'a str, Value, E> {
+    alt((
+        map(string, Value::String),
+        map(|i| boolean(ctx, i), Value::Bool),
+        |i| number(ctx, i),
+        map(double, Value::Float),
+    ))
+    .parse(i)
+}
+
 pub fn root<
     'a,
     E: ParseError<&'a str>
         + ContextError<&'a str>
-        + FromExternalError<&'a str, std::num::ParseIntError>,
+        + FromExternalError<&'a str, std::num::ParseIntError>
+        + FromExternalError<&'a str, Invalid>,
 >(
+    ctx: &Context,
     i: &'a str,
 ) -> IResult<&'a str, Value, E> {
-    delimited(sp, map(key_value, |(_, v)| v), opt(sp)).parse(i)
+    // With optional terminators, only the end of input marks where the
+    // setting stops. The name of the root setting is not part of any path.
+    delimited(
+        preceded(sp, key),
+        |i| setting_value(ctx, i),
+        preceded(sp, eof),
+    )
+    .parse(i)
+}
+
+/// Parses a whole libconfig document: any number of top-level settings,
+/// returned in the order they appear.
+pub fn document<
+    'a,
+    E: ParseError<&'a str>
+        + ContextError<&'a str>
+        + FromExternalError<&'a str, std::num::ParseIntError>
+        + FromExternalError<&'a str, Invalid>,
+>(
+    ctx: &Context,
+    i: &'a str,
+) -> IResult<&'a str, IndexMap<String, Value>, E> {
+    terminated(|i| settings(ctx, i), preceded(sp, eof)).parse(i)
 }
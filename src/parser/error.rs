@@ -0,0 +1,162 @@
+use crate::ParseError;
+use nom::error::{ContextError, ErrorKind, FromExternalError};
+use std::{cmp::Ordering, num::ParseIntError};
+
+/// Failures detected while parsing that are not a mismatch with the grammar.
+#[derive(Debug)]
+pub enum Invalid {
+    UnterminatedComment,
+    IntegerOverflow,
+    /// A `+` or `-` before a hex, binary or octal literal.
+    SignedRadix,
+    Utf8,
+    Include(String),
+    IncludedFile(Box<ParseError>),
+    /// A setting name used twice in a group; `first` is the length of the
+    /// input remaining at its first use, or at the `@include` that set it.
+    Duplicate {
+        key: String,
+        first: Option<usize>,
+    },
+}
+
+/// The error type of the parser functions, collecting what was expected at
+/// the furthest position reached. Converted into a [`ParseError`] once the
+/// whole input is known.
+#[derive(Debug)]
+pub struct Error<'a> {
+    input: &'a str,
+    context: Vec<&'static str>,
+    expected: Vec<String>,
+    reason: Option<String>,
+    included: Option<Box<ParseError>>,
+    first: Option<usize>,
+}
+
+impl<'a> Error<'a> {
+    fn new(input: &'a str, expected: Option<String>) -> Self {
+        Error {
+            input,
+            context: Vec::new(),
+            expected: expected.into_iter().collect(),
+            reason: None,
+            included: None,
+            first: None,
+        }
+    }
+
+    /// Locate the error in `input`, the text the parser was started on.
+    pub fn into_parse_error(self, input: &str) -> ParseError {
+        if let Some(included) = self.included {
+            return *included;
+        }
+
+        let mut context = self.context;
+        context.reverse();
+
+        let mut expected = self.expected;
+        expected.sort();
+        expected.dedup();
+
+        let e = ParseError::new(
+            input,
+            input.len() - self.input.len(),
+            context,
+            expected,
+            self.reason,
+        );
+        match self.first {
+            Some(first) => e.with_first_location(input, input.len() - first),
+            None => e,
+        }
+    }
+}
+
+impl<'a> nom::error::ParseError<&'a str> for Error<'a> {
+    fn from_error_kind(input: &'a str, kind: ErrorKind) -> Self {
+        let expected = match kind {
+            ErrorKind::Eof => Some("end of input".into()),
+            _ => None,
+        };
+        Error::new(input, expected)
+    }
+
+    fn append(_input: &'a str, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    fn from_char(input: &'a str, c: char) -> Self {
+        Error::new(input, Some(format!("'{c}'")))
+    }
+
+    fn or(mut self, other: Self) -> Self {
+        // Keep whichever alternative got furthest, merging the expectations
+        // of alternatives that failed at the same place.
+        match self.input.len().cmp(&other.input.len()) {
+            Ordering::Less => self,
+            Ordering::Greater => other,
+            Ordering::Equal => {
+                self.expected.extend(other.expected);
+                self
+            }
+        }
+    }
+}
+
+impl<'a> ContextError<&'a str> for Error<'a> {
+    fn add_context(input: &'a str, ctx: &'static str, mut other: Self) -> Self {
+        if other.input.len() == input.len() && other.reason.is_none() {
+            // Nothing was consumed, so what this context parses is what was
+            // expected here.
+            other.expected = vec![ctx.into()];
+        } else {
+            other.context.push(ctx);
+        }
+        other
+    }
+}
+
+impl<'a> FromExternalError<&'a str, ParseIntError> for Error<'a> {
+    fn from_external_error(input: &'a str, _kind: ErrorKind, e: ParseIntError) -> Self {
+        Error {
+            reason: Some(e.to_string()),
+            ..Error::new(input, None)
+        }
+    }
+}
+
+impl<'a> FromExternalError<&'a str, Invalid> for Error<'a> {
+    fn from_external_error(input: &'a str, _kind: ErrorKind, e: Invalid) -> Self {
+        match e {
+            Invalid::UnterminatedComment => Error {
+                reason: Some("unterminated block comment".into()),
+                ..Error::new(input, None)
+            },
+            Invalid::IntegerOverflow => Error {
+                reason: Some("integer does not fit in 64 bits".into()),
+                ..Error::new(input, None)
+            },
+            Invalid::SignedRadix => Error {
+                reason: Some("hex, binary and octal integers cannot have a sign".into()),
+                ..Error::new(input, None)
+            },
+            Invalid::Utf8 => Error {
+                reason: Some("string escapes do not form valid UTF-8".into()),
+                ..Error::new(input, None)
+            },
+            Invalid::Include(reason) => Error {
+                reason: Some(reason),
+                ..Error::new(input, None)
+            },
+            Invalid::Duplicate { key, first } => Error {
+                reason: Some(format!("duplicate setting \"{key}\"")),
+                first,
+                ..Error::new(input, None)
+            },
+            Invalid::IncludedFile(e) => Error {
+                included: Some(e),
+                ..Error::new(input, None)
+            },
+        }
+    }
+}
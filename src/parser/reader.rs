@@ -0,0 +1,296 @@
+use super::{
+    Context, Error, Invalid, assignment, expected, include, key, libconfig_value, scalar, sp,
+    terminator,
+};
+use crate::{ParseError, Value};
+use indexmap::IndexMap;
+use nom::{
+    IResult, Parser,
+    character::complete::char,
+    combinator::eof,
+    error::{ContextError, ErrorKind, FromExternalError, ParseError as _},
+    sequence::preceded,
+};
+use std::{borrow::Cow, collections::HashMap};
+
+/// What the next value in the input is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Group,
+    Array,
+    List,
+    Scalar,
+}
+
+impl Kind {
+    fn context(self) -> &'static str {
+        match self {
+            Kind::Group => "map",
+            Kind::Array => "array",
+            Kind::List => "list",
+            Kind::Scalar => "value",
+        }
+    }
+
+    fn close(self) -> char {
+        match self {
+            Kind::Group => '}',
+            Kind::Array => ']',
+            _ => ')',
+        }
+    }
+}
+
+/// A value that is not a group, array or list. A string without escapes
+/// that is not joined to the next one is borrowed from the input.
+pub enum Scalar<'de> {
+    Str(&'de str),
+    Value(Value),
+}
+
+/// The next entry in a group.
+pub enum Setting<'de> {
+    /// A setting, read up to and including the `=` or `:` before its value.
+    Name(&'de str),
+    /// The settings of the files named by an `@include`.
+    Included(IndexMap<String, Value>),
+}
+
+/// A group, array or list the reader is inside.
+struct Open<'de> {
+    /// The input from the opening delimiter, for the context of errors
+    at: &'de str,
+    kind: Kind,
+    /// How many elements of an array or list have been started
+    elements: usize,
+    /// For a group, where each setting in it was set, as in
+    /// [`settings`](super::settings)
+    names: HashMap<Cow<'de, str>, usize>,
+}
+
+/// Reads libconfig text a piece at a time, in the order a deserializer asks
+/// for it, instead of building a [`Value`] first.
+pub struct Reader<'de> {
+    ctx: Context,
+    input: &'de str,
+    rest: &'de str,
+    open: Vec<Open<'de>>,
+}
+
+impl<'de> Reader<'de> {
+    pub fn new(ctx: Context, input: &'de str) -> Self {
+        Reader {
+            ctx,
+            input,
+            rest: input,
+            open: Vec::new(),
+        }
+    }
+
+    pub fn input(&self) -> &'de str {
+        self.input
+    }
+
+    /// Run `parser` on the rest of the input, moving past what it reads.
+    fn run<O>(
+        &mut self,
+        parser: impl FnOnce(&Context, &'de str) -> IResult<&'de str, O, Error<'de>>,
+    ) -> Result<O, ParseError> {
+        match parser(&self.ctx, self.rest) {
+            Ok((rest, o)) => {
+                self.rest = rest;
+                Ok(o)
+            }
+            Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(self.fail(e)),
+            Err(nom::Err::Incomplete(_)) => unreachable!("complete parsers only"),
+        }
+    }
+
+    /// Locates `e`, adding the groups, arrays and lists it is inside to its
+    /// context as the parser does.
+    fn fail(&self, e: Error<'de>) -> ParseError {
+        self.open
+            .iter()
+            .rev()
+            .fold(e, |e, open| {
+                Error::add_context(open.at, open.kind.context(), e)
+            })
+            .into_parse_error(self.input)
+    }
+
+    fn skip_space(&mut self) -> Result<(), ParseError> {
+        self.run(|_, i| sp(i)).map(drop)
+    }
+
+    /// The byte offset of the next value.
+    pub fn offset(&mut self) -> Result<usize, ParseError> {
+        self.skip_space()?;
+        Ok(self.input.len() - self.rest.len())
+    }
+
+    /// Read the name of the root setting, up to its value.
+    pub fn root(&mut self) -> Result<(), ParseError> {
+        self.run(|_, i| preceded(sp, key).parse(i))?;
+        self.run(|_, i| assignment(i)).map(drop)
+    }
+
+    /// Read the terminator of the root setting and check that nothing
+    /// follows it.
+    pub fn end(&mut self) -> Result<(), ParseError> {
+        self.end_setting()?;
+        self.run(|_, i| preceded(sp, eof).parse(i)).map(drop)
+    }
+
+    pub fn peek(&mut self) -> Result<Kind, ParseError> {
+        self.skip_space()?;
+        Ok(match self.rest.chars().next() {
+            Some('{') => Kind::Group,
+            Some('[') => Kind::Array,
+            Some('(') => Kind::List,
+            _ => Kind::Scalar,
+        })
+    }
+
+    /// Move into the group, array or list that [`Reader::peek`] found.
+    pub fn open(&mut self, kind: Kind) {
+        self.open.push(Open {
+            at: self.rest,
+            kind,
+            elements: 0,
+            names: HashMap::new(),
+        });
+        self.rest = &self.rest[1..];
+    }
+
+    /// Move to the next element of the array or list being read. Returns
+    /// `false`, having read the closing bracket, when there are no more.
+    pub fn next_element(&mut self) -> Result<bool, ParseError> {
+        self.skip_space()?;
+        let open = self.open.last().expect("inside an array or list");
+        let close = open.kind.close();
+
+        let mut at_end = self.rest.starts_with(close);
+        if open.elements > 0 && !at_end {
+            // A comma, or else the closing bracket is what was expected.
+            self.run(|_, i| char(',').parse(i))
+                .map_err(|_| self.fail(Error::from_char(self.rest, close)))?;
+            self.skip_space()?;
+            at_end = self.rest.starts_with(close);
+        }
+        if at_end {
+            self.rest = &self.rest[1..];
+            self.open.pop();
+            return Ok(false);
+        }
+
+        self.open.last_mut().unwrap().elements += 1;
+        Ok(true)
+    }
+
+    /// Read up to the value of the next setting in the group being read.
+    /// Returns `None`, having read the closing brace, when there are no more.
+    pub fn next_setting(&mut self) -> Result<Option<Setting<'de>>, ParseError> {
+        self.skip_space()?;
+        if let Some(rest) = self.rest.strip_prefix('}') {
+            self.rest = rest;
+            self.open.pop();
+            return Ok(None);
+        }
+
+        let at = self.rest;
+        if at.starts_with("@include") {
+            let included = self.run(include)?;
+            let names = &mut self.open.last_mut().expect("inside a group").names;
+            for key in included.keys() {
+                if let Some(&first) = names.get(key.as_str()) {
+                    let key = key.clone();
+                    return Err(self.duplicate(at, key, first));
+                }
+                names.insert(Cow::Owned(key.clone()), at.len());
+            }
+            return Ok(Some(Setting::Included(included)));
+        }
+
+        let name = match key::<Error>(at) {
+            Ok((rest, name)) => {
+                self.rest = rest;
+                name
+            }
+            // Anything but a setting ends the group.
+            Err(_) => return Err(self.fail(Error::from_char(at, '}'))),
+        };
+        let names = &mut self.open.last_mut().expect("inside a group").names;
+        if let Some(&first) = names.get(name) {
+            return Err(self.duplicate(at, name.into(), first));
+        }
+        names.insert(Cow::Borrowed(name), at.len());
+
+        self.run(|_, i| assignment(i))?;
+        Ok(Some(Setting::Name(name)))
+    }
+
+    fn duplicate(&self, at: &'de str, key: String, first: usize) -> ParseError {
+        let first = Some(first);
+        let e =
+            Error::from_external_error(at, ErrorKind::Verify, Invalid::Duplicate { key, first });
+        self.fail(e)
+    }
+
+    /// Read the terminator after the value of a setting.
+    pub fn end_setting(&mut self) -> Result<(), ParseError> {
+        self.run(terminator).map(drop)
+    }
+
+    pub fn scalar(&mut self) -> Result<Scalar<'de>, ParseError> {
+        self.skip_space()?;
+        if let Some(s) = self.borrowed_str() {
+            return Ok(Scalar::Str(s));
+        }
+        self.run(|ctx, i| expected("value", |i| scalar(ctx, i)).parse(i))
+            .map(Scalar::Value)
+    }
+
+    /// Read a string literal with no escapes, unless another literal follows
+    /// that it is joined to.
+    fn borrowed_str(&mut self) -> Option<&'de str> {
+        let body = self.rest.strip_prefix('"')?;
+        let end = body.find(['"', '\\'])?;
+        if !body[end..].starts_with('"') {
+            return None;
+        }
+        let rest = &body[end + 1..];
+        let (next, _) = sp::<Error>(rest).ok()?;
+        if next.starts_with('"') {
+            return None;
+        }
+        self.rest = rest;
+        Some(&body[..end])
+    }
+
+    /// Read past the next value.
+    pub fn skip(&mut self) -> Result<(), ParseError> {
+        self.run(libconfig_value).map(drop)
+    }
+
+    /// Read past what is left of the group, array or list being read.
+    pub fn close(&mut self) -> Result<(), ParseError> {
+        let kind = self
+            .open
+            .last()
+            .expect("inside a group, array or list")
+            .kind;
+        if kind == Kind::Group {
+            while let Some(setting) = self.next_setting()? {
+                if let Setting::Name(_) = setting {
+                    self.skip()?;
+                    self.end_setting()?;
+                }
+            }
+        } else {
+            while self.next_element()? {
+                self.skip()?;
+            }
+        }
+        Ok(())
+    }
+}
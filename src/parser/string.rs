@@ -1,10 +1,11 @@
+use super::Invalid;
 use nom::{
     IResult, Parser,
     branch::alt,
-    bytes::streaming::{is_not, take_while_m_n},
-    character::streaming::{char, multispace1},
+    bytes::complete::{is_not, take_while_m_n},
+    character::complete::{char, multispace1},
     combinator::{map, map_opt, map_res, value, verify},
-    error::{FromExternalError, ParseError},
+    error::{ErrorKind, FromExternalError, ParseError},
     multi::fold_many0,
     sequence::{delimited, preceded},
 };
@@ -22,6 +23,23 @@ where
     map_opt(parse_u32, std::char::from_u32).parse(input)
 }
 
+/// Parse libconfig's `\xNN` escape, which stands for a single byte.
+fn parse_hex_byte<'a, E>(input: &'a str) -> IResult<&'a str, u8, E>
+where
+    E: ParseError<&'a str> + FromExternalError<&'a str, std::num::ParseIntError>,
+{
+    let parse_hex = take_while_m_n(2, 2, |c: char| c.is_ascii_hexdigit());
+
+    preceded(
+        char('\\'),
+        preceded(
+            char('x'),
+            map_res(parse_hex, |hex| u8::from_str_radix(hex, 16)),
+        ),
+    )
+    .parse(input)
+}
+
 /// Parse an escaped character: \n, \t, \r, \u{00AC}, etc.
 fn parse_escaped_char<'a, E>(input: &'a str) -> IResult<&'a str, char, E>
 where
@@ -66,6 +84,7 @@ fn parse_literal<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str,
 enum StringFragment<'a> {
     Literal(&'a str),
     EscapedChar(char),
+    EscapedByte(u8),
     EscapedWS,
 }
 
@@ -77,6 +96,7 @@ where
 {
     alt((
         map(parse_literal, StringFragment::Literal),
+        map(parse_hex_byte, StringFragment::EscapedByte),
         map(parse_escaped_char, StringFragment::EscapedChar),
         value(StringFragment::EscapedWS, parse_escaped_whitespace),
     ))
@@ -84,19 +104,33 @@ where
 }
 
 /// Parse a string. Use a loop of parse_fragment and push all of the fragments
-/// into an output string.
+/// into an output string. `\xNN` escapes are bytes, so together they may
+/// spell out a multi-byte UTF-8 character; the result must be valid UTF-8.
 pub fn parse<'a, E>(input: &'a str) -> IResult<&'a str, String, E>
 where
-    E: ParseError<&'a str> + FromExternalError<&'a str, std::num::ParseIntError>,
+    E: ParseError<&'a str>
+        + FromExternalError<&'a str, std::num::ParseIntError>
+        + FromExternalError<&'a str, Invalid>,
 {
-    let build_string = fold_many0(parse_fragment, String::new, |mut string, fragment| {
+    let build_string = fold_many0(parse_fragment, Vec::new, |mut bytes, fragment| {
         match fragment {
-            StringFragment::Literal(s) => string.push_str(s),
-            StringFragment::EscapedChar(c) => string.push(c),
+            StringFragment::Literal(s) => bytes.extend_from_slice(s.as_bytes()),
+            StringFragment::EscapedChar(c) => {
+                bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes())
+            }
+            StringFragment::EscapedByte(b) => bytes.push(b),
             StringFragment::EscapedWS => {}
         }
-        string
+        bytes
     });
 
-    delimited(char('"'), build_string, char('"')).parse(input)
+    let (rest, bytes) = delimited(char('"'), build_string, char('"')).parse(input)?;
+    match String::from_utf8(bytes) {
+        Ok(string) => Ok((rest, string)),
+        Err(_) => Err(nom::Err::Failure(E::from_external_error(
+            input,
+            ErrorKind::MapRes,
+            Invalid::Utf8,
+        ))),
+    }
 }
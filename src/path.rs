@@ -0,0 +1,109 @@
+use crate::{LookupError, LookupErrorKind, Value};
+
+/// Characters that separate the segments of a setting path, as in libconfig.
+const SEPARATORS: &[char] = &['.', ':', '/'];
+
+/// One step of a setting path: a setting name or a `[n]` element index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+impl Segment<'_> {
+    pub fn get<'v>(&self, value: &'v Value) -> Result<&'v Value, LookupErrorKind> {
+        match (*self, value) {
+            (Segment::Key(key), Value::Object(o)) => o.get(key).ok_or(LookupErrorKind::MissingKey),
+            (Segment::Key(_), _) => Err(LookupErrorKind::NotAGroup),
+            (Segment::Index(index), Value::Array(a, _)) => {
+                a.get(index).ok_or(LookupErrorKind::IndexOutOfBounds {
+                    index,
+                    len: a.len(),
+                })
+            }
+            (Segment::Index(index), Value::Object(o)) => {
+                o.get_index(index)
+                    .map(|(_, v)| v)
+                    .ok_or(LookupErrorKind::IndexOutOfBounds {
+                        index,
+                        len: o.len(),
+                    })
+            }
+            (Segment::Index(_), _) => Err(LookupErrorKind::NotAggregate),
+        }
+    }
+
+    pub fn get_mut<'v>(&self, value: &'v mut Value) -> Result<&'v mut Value, LookupErrorKind> {
+        match (*self, value) {
+            (Segment::Key(key), Value::Object(o)) => {
+                o.get_mut(key).ok_or(LookupErrorKind::MissingKey)
+            }
+            (Segment::Key(_), _) => Err(LookupErrorKind::NotAGroup),
+            (Segment::Index(index), Value::Array(a, _)) => {
+                let len = a.len();
+                a.get_mut(index)
+                    .ok_or(LookupErrorKind::IndexOutOfBounds { index, len })
+            }
+            (Segment::Index(index), Value::Object(o)) => {
+                let len = o.len();
+                o.get_index_mut(index)
+                    .map(|(_, v)| v)
+                    .ok_or(LookupErrorKind::IndexOutOfBounds { index, len })
+            }
+            (Segment::Index(_), _) => Err(LookupErrorKind::NotAggregate),
+        }
+    }
+}
+
+/// Splits `path` into its segments, each with its byte range in `path`.
+///
+/// Segments are separated by `.`, `:` or `/`, and an index may also follow a
+/// name directly, so `a.b.[0].c` and `a.b[0].c` are the same path. The empty
+/// path has no segments and names the value it is looked up in.
+pub fn parse(path: &str) -> Result<Vec<(Segment<'_>, usize, usize)>, LookupError> {
+    let mut segments = Vec::new();
+    if path.is_empty() {
+        return Ok(segments);
+    }
+
+    let mut start = 0;
+    for part in path.split(SEPARATORS) {
+        let end = start + part.len();
+        let invalid = |from: usize| LookupError::new(path, from, end, LookupErrorKind::InvalidPath);
+
+        let name_len = part.find('[').unwrap_or(part.len());
+        if name_len > 0 {
+            segments.push((Segment::Key(&part[..name_len]), start, start + name_len));
+        } else if part.is_empty() {
+            return Err(invalid(start));
+        }
+
+        let mut offset = start + name_len;
+        let mut rest = &part[name_len..];
+        while !rest.is_empty() {
+            let index = rest
+                .strip_prefix('[')
+                .and_then(|r| r.split_once(']'))
+                .and_then(|(index, r)| Some((index.parse::<usize>().ok()?, r)));
+            let Some((index, r)) = index else {
+                return Err(invalid(offset));
+            };
+            let len = rest.len() - r.len();
+            segments.push((Segment::Index(index), offset, offset + len));
+            offset += len;
+            rest = r;
+        }
+
+        start = end + 1;
+    }
+    Ok(segments)
+}
+
+/// The byte range of the last segment of `path`, where errors about the
+/// setting the whole path names are reported.
+pub fn last_span(path: &str) -> (usize, usize) {
+    parse(path)
+        .ok()
+        .and_then(|segments| segments.last().map(|&(_, start, end)| (start, end)))
+        .unwrap_or((0, 0))
+}
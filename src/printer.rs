@@ -1,13 +1,188 @@
-use crate::{ArrayType, Value};
-use std::fmt::Write;
+use crate::{ArrayType, Assignment, EscapeStyle, FormatOptions, Indent, IntFormat, Value};
+use indexmap::IndexMap;
+use std::fmt::{self, Write};
 
-fn indent(w: &mut impl Write, indentation_level: i32) {
-    for _ in 0..indentation_level {
+/// How many columns a tab is taken to fill when fitting lines to
+/// [`FormatOptions::max_width`].
+const TAB_WIDTH: usize = 8;
+
+/// The number of columns `s` fills.
+pub fn line_width(s: &str) -> usize {
+    s.chars()
+        .map(|c| if c == '\t' { TAB_WIDTH } else { 1 })
+        .sum()
+}
+
+/// The number of columns of indentation for `depth` levels of nesting.
+pub fn indent_width(depth: usize, options: &FormatOptions) -> usize {
+    match options.indent {
+        Indent::Spaces(n) => depth * n,
+        Indent::Tabs => depth * TAB_WIDTH,
+    }
+}
+
+/// Whether an array or list written as `inline` starting at `column`, and the
+/// `,` or `;` after it, fit in the configured width.
+pub fn fits_inline(inline: &str, column: usize, options: &FormatOptions) -> bool {
+    match options.max_width {
+        Some(width) => !inline.contains('\n') && column + line_width(inline) < width,
+        None => true,
+    }
+}
+
+/// Writes the elements of an array or list at `depth` between `open` and
+/// `close`, all on one line if they fit after `column` or else one per line.
+pub fn print_elements(
+    w: &mut impl Write,
+    elements: &[String],
+    (open, close): (char, char),
+    depth: usize,
+    column: usize,
+    options: &FormatOptions,
+) {
+    let inline = if elements.is_empty() {
+        format!("{open} {close}")
+    } else {
+        format!("{open} {} {close}", elements.join(", "))
+    };
+    if fits_inline(&inline, column, options) {
+        write!(w, "{inline}").unwrap();
+        return;
+    }
+
+    write!(w, "{open}").unwrap();
+    for (i, element) in elements.iter().enumerate() {
+        if i > 0 {
+            write!(w, ",").unwrap();
+        }
+        writeln!(w).unwrap();
+        indent(w, depth + 1, options);
+        write!(w, "{element}").unwrap();
+    }
+    writeln!(w).unwrap();
+    indent(w, depth, options);
+    write!(w, "{close}").unwrap();
+}
+
+/// Writes the indentation for `depth` levels of nesting.
+pub fn indent(w: &mut impl Write, depth: usize, options: &FormatOptions) {
+    match options.indent {
+        Indent::Spaces(n) => write!(w, "{:1$}", "", depth * n).unwrap(),
+        Indent::Tabs => write!(w, "{:\t<1$}", "", depth).unwrap(),
+    }
+}
+
+/// Writes what goes between the name of a setting at `depth` and its value,
+/// e.g. ` = ` or, for a group with the brace on its own line, ` :` and a line
+/// break.
+pub fn print_assignment(w: &mut impl Write, group: bool, depth: usize, options: &FormatOptions) {
+    let colon = options.assignment == Assignment::Colon || (group && options.colon_for_groups);
+    write!(w, " {}", if colon { ':' } else { '=' }).unwrap();
+    if group && options.brace_on_own_line {
+        writeln!(w).unwrap();
+        indent(w, depth, options);
+    } else {
         write!(w, " ").unwrap();
     }
 }
 
-pub fn print(w: &mut impl Write, value: &Value, indentation_level: i32) {
+/// Writes a float so that it reads back as a float, with the configured
+/// precision. Like libconfig, a fixed precision switches to an exponent for
+/// magnitudes below 1e-4 or from 1e10 up, instead of writing long runs of
+/// zeroes or rounding to zero.
+pub fn print_float<F: fmt::Debug + fmt::Display + fmt::LowerExp + Into<f64> + Copy>(
+    w: &mut impl Write,
+    f: F,
+    options: &FormatOptions,
+) {
+    let magnitude = f.into().abs();
+    let s = match options.float_precision {
+        Some(digits) if magnitude != 0.0 && !(1e-4..1e10).contains(&magnitude) => {
+            format!("{f:.digits$e}")
+        }
+        Some(digits) => format!("{f:.digits$}"),
+        None => format!("{f:?}"),
+    };
+    write!(w, "{s}").unwrap();
+    if !s.contains(['.', 'e', 'E', 'i', 'N']) {
+        write!(w, ".0").unwrap();
+    }
+}
+
+/// Writes an integer literal in the given radix. libconfig has no signed hex,
+/// binary or octal literals, so negative values are written in those as their
+/// two's complement in `bits` bits.
+pub fn print_int(w: &mut impl Write, value: i128, bits: u32, format: IntFormat) {
+    let unsigned = if value < 0 {
+        (value + (1 << bits)) as u128
+    } else {
+        value as u128
+    };
+    match format {
+        IntFormat::Decimal => write!(w, "{value}").unwrap(),
+        IntFormat::Hex => write!(w, "0x{unsigned:X}").unwrap(),
+        IntFormat::Binary => write!(w, "0b{unsigned:b}").unwrap(),
+        IntFormat::Octal => write!(w, "0o{unsigned:o}").unwrap(),
+    }
+}
+
+/// Writes `s` as a quoted string literal using the given escapes.
+pub fn print_str(w: &mut impl Write, s: &str, style: EscapeStyle) {
+    if style == EscapeStyle::Rust {
+        write!(w, "{s:?}").unwrap();
+        return;
+    }
+
+    write!(w, "\"").unwrap();
+    for c in s.chars() {
+        match c {
+            '\\' => write!(w, "\\\\"),
+            '"' => write!(w, "\\\""),
+            '\n' => write!(w, "\\n"),
+            '\r' => write!(w, "\\r"),
+            '\t' => write!(w, "\\t"),
+            '\u{0C}' => write!(w, "\\f"),
+            c if c.is_ascii_control() => write!(w, "\\x{:02X}", u32::from(c)),
+            c => write!(w, "{c}"),
+        }
+        .unwrap();
+    }
+    write!(w, "\"").unwrap();
+}
+
+/// Splits `s` into the pieces written as adjacent string literals when it is
+/// longer than the configured width.
+pub fn split_string<'a>(s: &'a str, options: &FormatOptions) -> Vec<&'a str> {
+    let Some(width) = options.string_width else {
+        return vec![s];
+    };
+
+    let mut pieces = Vec::new();
+    let mut rest = s;
+    while rest.chars().count() > width {
+        let end = rest
+            .char_indices()
+            .nth(width)
+            .map_or(rest.len(), |(i, _)| i);
+        let end = match rest[..end].rfind(' ') {
+            Some(space) if space > 0 => space + 1,
+            _ => end,
+        };
+        pieces.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+    pieces.push(rest);
+    pieces
+}
+
+/// Writes `value` as the value of a setting at `depth`, starting at `column`.
+pub fn print(
+    w: &mut impl Write,
+    value: &Value,
+    depth: usize,
+    column: usize,
+    options: &FormatOptions,
+) {
     match value {
         Value::Bool(b) => {
             if *b {
@@ -16,43 +191,100 @@ pub fn print(w: &mut impl Write, value: &Value, indentation_level: i32) {
                 write!(w, "false").unwrap();
             }
         }
-        Value::Int(i) => {
-            write!(w, "{i}").unwrap();
+        Value::Int(i, format) => {
+            print_int(w, i128::from(*i), 32, *format);
+        }
+        Value::Int64(i, format) => {
+            print_int(w, i128::from(*i), 64, *format);
+            write!(w, "L").unwrap();
+        }
+        Value::UInt64(i, format) => {
+            print_int(w, i128::from(*i), 64, *format);
+            write!(w, "L").unwrap();
         }
         Value::Float(f) => {
-            write!(w, "{f:?}").unwrap();
+            print_float(w, *f, options);
         }
         Value::String(s) => {
-            write!(w, "{s:?}").unwrap();
-        }
-        Value::Array(a, array_type) => {
-            if *array_type == ArrayType::List {
-                write!(w, "( ").unwrap();
-            } else {
-                write!(w, "[ ").unwrap();
-            }
-            for (i, v) in a.iter().enumerate() {
-                print(w, v, indentation_level + 4);
-                if i != a.len() - 1 {
-                    write!(w, ", ").unwrap();
+            for (i, piece) in split_string(s, options).into_iter().enumerate() {
+                if i > 0 {
+                    writeln!(w).unwrap();
+                    indent(w, depth + 1, options);
                 }
-            }
-            if *array_type == ArrayType::List {
-                write!(w, " )").unwrap();
-            } else {
-                write!(w, " ]").unwrap();
+                print_str(w, piece, options.escapes);
             }
         }
+        Value::Array(a, array_type) => {
+            // Elements are laid out the same whether they end up inline or
+            // one per line, so each is only written once.
+            let elements = a
+                .iter()
+                .map(|v| {
+                    let mut element = String::new();
+                    print(
+                        &mut element,
+                        v,
+                        depth + 1,
+                        indent_width(depth + 1, options),
+                        options,
+                    );
+                    element
+                })
+                .collect::<Vec<_>>();
+            let delimiters = match array_type {
+                ArrayType::Array => ('[', ']'),
+                ArrayType::List => ('(', ')'),
+            };
+            print_elements(w, &elements, delimiters, depth, column, options);
+        }
         Value::Object(o) => {
             writeln!(w, "{{").unwrap();
             for (name, v) in o {
-                indent(w, indentation_level);
-                write!(w, "{name} : ").unwrap();
-                print(w, v, indentation_level + 4);
-                writeln!(w, ";").unwrap();
+                print_setting(w, name, v, depth + 1, options);
+                writeln!(w).unwrap();
             }
-            indent(w, indentation_level - 4);
+            indent(w, depth, options);
             write!(w, "}}").unwrap();
         }
     }
 }
+
+/// Writes an indented setting at `depth`, without the line break after it.
+pub fn print_setting(
+    w: &mut impl Write,
+    name: &str,
+    value: &Value,
+    depth: usize,
+    options: &FormatOptions,
+) {
+    let mut prefix = String::new();
+    indent(&mut prefix, depth, options);
+    write!(prefix, "{name}").unwrap();
+    print_assignment(
+        &mut prefix,
+        matches!(value, Value::Object(_)),
+        depth,
+        options,
+    );
+    write!(w, "{prefix}").unwrap();
+    let column = prefix.rsplit('\n').next().map_or(0, line_width);
+    print(w, value, depth, column, options);
+    write!(w, "{}", options.terminator.as_str()).unwrap();
+}
+
+/// Writes each setting at the top level, one per line, as in a libconfig file.
+pub fn print_document(
+    w: &mut impl Write,
+    settings: &IndexMap<String, Value>,
+    options: &FormatOptions,
+) {
+    for (i, (name, v)) in settings.iter().enumerate() {
+        if i > 0 {
+            writeln!(w).unwrap();
+        }
+        print_setting(w, name, v, 0, options);
+    }
+    if !settings.is_empty() && options.trailing_newline {
+        writeln!(w).unwrap();
+    }
+}
@@ -1,10 +1,24 @@
-use super::error::Error;
-use crate::Value;
+use super::stream::Deserializer;
+use super::{
+    error::{Error, ErrorKind},
+    value::{ARRAY_TOKEN, INT64_TOKEN, LIST_TOKEN, VALUE_TOKEN},
+};
+use crate::{
+    ArrayType, DuplicateSettings, ParseOptions, Value, error::line_column, parser, path::Segment,
+};
+use nom::Finish;
 use serde::{
     Deserialize,
-    de::{self, DeserializeSeed, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor},
+    de::{
+        self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess,
+        SeqAccess, VariantAccess, Visitor, value::I64Deserializer,
+    },
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::{self, Write},
+    marker::PhantomData,
 };
-use std::{collections::VecDeque, marker::PhantomData, str::FromStr};
 
 // Example
 // https://serde.rs/data-format.html
@@ -14,9 +28,11 @@ use std::{collections::VecDeque, marker::PhantomData, str::FromStr};
 enum Token {
     Bool(bool),
     Int(i64),
+    Int64(i64),
+    UInt(u64),
     Float(f64),
     String(String),
-    SeqCount(usize),
+    SeqCount(usize, ArrayType),
     MapCount(usize),
 }
 
@@ -28,16 +44,23 @@ impl Token {
         }
     }
 
-    fn into_int(self) -> Result<i64, Token> {
-        match self {
-            Token::Int(v) => Ok(v),
-            _ => Err(self),
-        }
+    /// Converts an int, or with `autoconvert` a float, to the integer type
+    /// `T` named by `expected`, if it is in range.
+    fn into_int<T: TryFrom<i128>>(self, autoconvert: bool, expected: &str) -> Result<T, Error> {
+        let v = match self {
+            Token::Int(v) | Token::Int64(v) => i128::from(v),
+            Token::UInt(v) => i128::from(v),
+            Token::Float(v) if autoconvert => v as i128,
+            _ => return Err(invalid_type(&self, "an integer")),
+        };
+        T::try_from(v).map_err(|_| invalid_value(&self, expected))
     }
 
-    fn into_float(self) -> Result<f64, Token> {
+    fn into_float(self, autoconvert: bool) -> Result<f64, Token> {
         match self {
             Token::Float(v) => Ok(v),
+            Token::Int(v) | Token::Int64(v) if autoconvert => Ok(v as f64),
+            Token::UInt(v) if autoconvert => Ok(v as f64),
             _ => Err(self),
         }
     }
@@ -51,19 +74,62 @@ impl Token {
 
     fn into_count(self) -> Result<usize, Token> {
         match self {
-            Token::SeqCount(v) | Token::MapCount(v) => Ok(v),
+            Token::SeqCount(v, _) | Token::MapCount(v) => Ok(v),
             _ => Err(self),
         }
     }
 }
 
+/// Describes a token the way serde's `Unexpected` does.
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Bool(v) => write!(f, "bool `{v}`"),
+            Token::Int(v) => write!(f, "int `{v}`"),
+            Token::Int64(v) => write!(f, "int64 `{v}`"),
+            Token::UInt(v) => write!(f, "int `{v}`"),
+            Token::Float(v) => write!(f, "float `{v}`"),
+            Token::String(v) => write!(f, "string {v:?}"),
+            Token::SeqCount(_, ArrayType::Array) => write!(f, "array"),
+            Token::SeqCount(_, ArrayType::List) => write!(f, "list"),
+            Token::MapCount(_) => write!(f, "group"),
+        }
+    }
+}
+
+fn end_of_input() -> Error {
+    ErrorKind::Message("Reached end of input!".into()).into()
+}
+
+fn invalid_type(found: &Token, expected: &str) -> Error {
+    ErrorKind::InvalidType {
+        expected: expected.into(),
+        found: found.to_string(),
+    }
+    .into()
+}
+
+fn invalid_value(found: &Token, expected: &str) -> Error {
+    ErrorKind::InvalidValue {
+        expected: expected.into(),
+        found: found.to_string(),
+    }
+    .into()
+}
+
 fn flatten(res: &mut VecDeque<Token>, value: Value) {
     match value {
         Value::Bool(b) => {
             res.push_back(Token::Bool(b));
         }
-        Value::Int(i) => {
-            res.push_back(Token::Int(i));
+        Value::Int(i, _) => {
+            res.push_back(Token::Int(i.into()));
+        }
+        Value::Int64(i, _) => {
+            res.push_back(Token::Int64(i));
+        }
+        Value::UInt64(i, _) => {
+            res.push_back(Token::UInt(i));
         }
         Value::Float(f) => {
             res.push_back(Token::Float(f));
@@ -71,8 +137,8 @@ fn flatten(res: &mut VecDeque<Token>, value: Value) {
         Value::String(s) => {
             res.push_back(Token::String(s));
         }
-        Value::Array(a, _) => {
-            res.push_back(Token::SeqCount(a.len()));
+        Value::Array(a, array_type) => {
+            res.push_back(Token::SeqCount(a.len(), array_type));
             for v in a {
                 flatten(res, v)
             }
@@ -87,8 +153,17 @@ fn flatten(res: &mut VecDeque<Token>, value: Value) {
     }
 }
 
-pub struct Deserializer<'de> {
+/// Deserializes from the tokens of a [`Value`].
+pub(super) struct ValueDeserializer<'de> {
     tokens: VecDeque<Token>,
+    autoconvert: bool,
+    /// Set while deserializing a [`Value`], to pass on the type of arrays
+    in_value: bool,
+    /// The path of the setting being deserialized, for errors
+    path: String,
+    /// The text the value was parsed from, and where each setting in it
+    /// starts, for the location of errors
+    source: Option<(&'de str, HashMap<String, usize>)>,
     phantom: PhantomData<&'de str>,
 }
 
@@ -96,38 +171,176 @@ pub fn from_str<'a, T>(s: &'a str) -> Result<T, Error>
 where
     T: Deserialize<'a>,
 {
-    let value = crate::Value::from_str(s).map_err(|e| Error::Message(format!("{e:?}")))?;
+    from_str_with_options(s, &ParseOptions::default())
+}
 
-    let mut tokens = VecDeque::new();
+/// Like [`from_str`], with control over how the input is read.
+pub fn from_str_with_options<'a, T>(s: &'a str, options: &ParseOptions) -> Result<T, Error>
+where
+    T: Deserialize<'a>,
+{
+    // Which of several settings with the same name is kept is only known
+    // once the whole group has been read.
+    if options.duplicate_settings != DuplicateSettings::Error {
+        let ctx = parser::Context::new(options).recording_positions();
+        let value = parser::root::<parser::Error>(&ctx, s)
+            .finish()
+            .map(|(_, value)| value)
+            .map_err(|e| e.into_parse_error(s))?;
+
+        let mut de = ValueDeserializer::from_value(value, options.autoconvert);
+        de.source = Some((s, ctx.into_positions()));
+        return T::deserialize(&mut de).map_err(|e| de.place(e));
+    }
+
+    let mut de = Deserializer::with_options(s, options)?;
+    T::deserialize(&mut de)
+        .map_err(|e| de.place_root(e))
+        .and_then(|value| {
+            de.end()?;
+            Ok(value)
+        })
+        .map_err(|e| match e.kind() {
+            ErrorKind::Syntax(_) => e,
+            // A syntax error later in the input is reported in preference to
+            // what went wrong before it, as when parsing first.
+            _ => syntax_error(s, options).unwrap_or(e),
+        })
+}
 
-    flatten(&mut tokens, value);
+fn syntax_error(s: &str, options: &ParseOptions) -> Option<Error> {
+    let ctx = parser::Context::new(options);
+    let e = parser::root::<parser::Error>(&ctx, s).finish().err()?;
+    Some(e.into_parse_error(s).into())
+}
 
-    let mut deserializer = Deserializer::<'a> {
-        tokens,
-        phantom: PhantomData,
-    };
+/// Deserializes a `T` from a [`Value`] without going through text, e.g. after
+/// editing a parsed config with [`Value::set_path`].
+pub fn from_value<T>(value: Value) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let mut de = ValueDeserializer::from_value(value, false);
+    T::deserialize(&mut de).map_err(|e| de.place(e))
+}
 
-    T::deserialize(&mut deserializer)
+/// Like [`from_value`], leaving `value` in place.
+pub fn from_value_ref<T>(value: &Value) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    from_value(value.clone())
 }
 
-impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+impl ValueDeserializer<'_> {
+    fn from_value(value: Value, autoconvert: bool) -> Self {
+        let mut tokens = VecDeque::new();
+
+        flatten(&mut tokens, value);
+
+        ValueDeserializer {
+            tokens,
+            autoconvert,
+            in_value: false,
+            path: String::new(),
+            source: None,
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// Extends `path` by `segment`, returning the length to restore when leaving
+/// it.
+pub(super) fn enter(path: &mut String, segment: Segment) -> usize {
+    let len = path.len();
+    match segment {
+        Segment::Key(key) => {
+            if !path.is_empty() {
+                path.push('.');
+            }
+            path.push_str(key);
+        }
+        Segment::Index(index) => write!(path, "[{index}]").unwrap(),
+    }
+    len
+}
+
+impl<'de> ValueDeserializer<'de> {
+    /// Deserializes a value from an included file, found at `path` in the
+    /// text being deserialized.
+    pub(super) fn nested(value: Value, autoconvert: bool, in_value: bool, path: &str) -> Self {
+        ValueDeserializer {
+            in_value,
+            path: path.into(),
+            ..Self::from_value(value, autoconvert)
+        }
+    }
+
+    /// Extends the path of the setting being deserialized, returning the
+    /// length to restore with [`ValueDeserializer::leave`].
+    fn enter(&mut self, segment: Segment) -> usize {
+        enter(&mut self.path, segment)
+    }
+
+    /// Extends the path by the setting name in the next token, if it is one.
+    fn enter_key(&mut self) -> Option<usize> {
+        let key = self.next_key()?;
+        Some(self.enter(Segment::Key(&key)))
+    }
+
+    /// Places `res`, if it failed, at the current path and restores the path
+    /// to length `len`.
+    fn leave<T>(&mut self, len: Option<usize>, res: Result<T, Error>) -> Result<T, Error> {
+        let Some(len) = len else {
+            return res;
+        };
+        let res = res.map_err(|e| self.place(e));
+        self.path.truncate(len);
+        res
+    }
+
+    /// The name of the setting in the next token, when it is one.
+    fn next_key(&self) -> Option<String> {
+        match self.tokens.front() {
+            Some(Token::String(key)) => Some(key.clone()),
+            _ => None,
+        }
+    }
+
+    pub(super) fn place(&self, e: Error) -> Error {
+        e.at(&self.path, || {
+            let (input, positions) = self.source.as_ref()?;
+            let offset = input.len() - positions.get(&self.path)?;
+            Some(line_column(input, offset))
+        })
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut ValueDeserializer<'de> {
     type Error = Error;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        let token = self
-            .tokens
-            .front()
-            .ok_or_else(|| Error::Message("Reached end of input!".into()))?;
+        let token = self.tokens.front().ok_or_else(end_of_input)?;
 
         match token {
             Token::Bool(_) => self.deserialize_bool(visitor),
-            Token::Int(_) => self.deserialize_i64(visitor),
+            Token::Int64(v) if self.in_value => {
+                let v = *v;
+                self.tokens.pop_front();
+                visitor.visit_map(MarkedSeq::int64(v))
+            }
+            Token::Int(_) | Token::Int64(_) => self.deserialize_i64(visitor),
+            Token::UInt(_) => self.deserialize_u64(visitor),
             Token::Float(_) => self.deserialize_f64(visitor),
             Token::String(_) => self.deserialize_string(visitor),
-            Token::SeqCount(_) => self.deserialize_seq(visitor),
+            Token::SeqCount(_, array_type) if self.in_value => {
+                let array_type = array_type.clone();
+                visitor.visit_map(MarkedSeq::new(self, array_type))
+            }
+            Token::SeqCount(..) => self.deserialize_seq(visitor),
             Token::MapCount(_) => self.deserialize_map(visitor),
         }
     }
@@ -136,159 +349,93 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        let token = self
-            .tokens
-            .pop_front()
-            .ok_or_else(|| Error::Message("Reached end of input!".into()))?;
+        let token = self.tokens.pop_front().ok_or_else(end_of_input)?;
 
-        visitor.visit_bool(
-            token
-                .into_bool()
-                .map_err(|t| Error::Message(format!("{t:?} is not a bool")))?,
-        )
+        visitor.visit_bool(token.into_bool().map_err(|t| invalid_type(&t, "a bool"))?)
     }
 
     fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        let token = self
-            .tokens
-            .pop_front()
-            .ok_or_else(|| Error::Message("Reached end of input!".into()))?;
+        let token = self.tokens.pop_front().ok_or_else(end_of_input)?;
 
-        visitor.visit_i8(
-            token
-                .into_int()
-                .map_err(|t| Error::Message(format!("{t:?} is not a integer")))? as i8,
-        )
+        visitor.visit_i8(token.into_int(self.autoconvert, "an i8")?)
     }
 
     fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        let token = self
-            .tokens
-            .pop_front()
-            .ok_or_else(|| Error::Message("Reached end of input!".into()))?;
+        let token = self.tokens.pop_front().ok_or_else(end_of_input)?;
 
-        visitor.visit_i16(
-            token
-                .into_int()
-                .map_err(|t| Error::Message(format!("{t:?} is not a integer")))? as i16,
-        )
+        visitor.visit_i16(token.into_int(self.autoconvert, "an i16")?)
     }
 
     fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        let token = self
-            .tokens
-            .pop_front()
-            .ok_or_else(|| Error::Message("Reached end of input!".into()))?;
+        let token = self.tokens.pop_front().ok_or_else(end_of_input)?;
 
-        visitor.visit_i32(
-            token
-                .into_int()
-                .map_err(|t| Error::Message(format!("{t:?} is not a integer")))? as i32,
-        )
+        visitor.visit_i32(token.into_int(self.autoconvert, "an i32")?)
     }
 
     fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        let token = self
-            .tokens
-            .pop_front()
-            .ok_or_else(|| Error::Message("Reached end of input!".into()))?;
+        let token = self.tokens.pop_front().ok_or_else(end_of_input)?;
 
-        visitor.visit_i64(
-            token
-                .into_int()
-                .map_err(|t| Error::Message(format!("{t:?} is not a integer")))?,
-        )
+        visitor.visit_i64(token.into_int(self.autoconvert, "an i64")?)
     }
 
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        let token = self
-            .tokens
-            .pop_front()
-            .ok_or_else(|| Error::Message("Reached end of input!".into()))?;
+        let token = self.tokens.pop_front().ok_or_else(end_of_input)?;
 
-        visitor.visit_u8(
-            token
-                .into_int()
-                .map_err(|t| Error::Message(format!("{t:?} is not a integer")))? as u8,
-        )
+        visitor.visit_u8(token.into_int(self.autoconvert, "a u8")?)
     }
 
     fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        let token = self
-            .tokens
-            .pop_front()
-            .ok_or_else(|| Error::Message("Reached end of input!".into()))?;
+        let token = self.tokens.pop_front().ok_or_else(end_of_input)?;
 
-        visitor.visit_u16(
-            token
-                .into_int()
-                .map_err(|t| Error::Message(format!("{t:?} is not a integer")))? as u16,
-        )
+        visitor.visit_u16(token.into_int(self.autoconvert, "a u16")?)
     }
 
     fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        let token = self
-            .tokens
-            .pop_front()
-            .ok_or_else(|| Error::Message("Reached end of input!".into()))?;
+        let token = self.tokens.pop_front().ok_or_else(end_of_input)?;
 
-        visitor.visit_u32(
-            token
-                .into_int()
-                .map_err(|t| Error::Message(format!("{t:?} is not a integer")))? as u32,
-        )
+        visitor.visit_u32(token.into_int(self.autoconvert, "a u32")?)
     }
 
     fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        let token = self
-            .tokens
-            .pop_front()
-            .ok_or_else(|| Error::Message("Reached end of input!".into()))?;
+        let token = self.tokens.pop_front().ok_or_else(end_of_input)?;
 
-        visitor.visit_u64(
-            token
-                .into_int()
-                .map_err(|t| Error::Message(format!("{t:?} is not a integer")))? as u64,
-        )
+        visitor.visit_u64(token.into_int(self.autoconvert, "a u64")?)
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        let token = self
-            .tokens
-            .pop_front()
-            .ok_or_else(|| Error::Message("Reached end of input!".into()))?;
+        let token = self.tokens.pop_front().ok_or_else(end_of_input)?;
 
         visitor.visit_f32(
             token
-                .into_float()
-                .map_err(|t| Error::Message(format!("{t:?} is not a float")))? as f32,
+                .into_float(self.autoconvert)
+                .map_err(|t| invalid_type(&t, "a float"))? as f32,
         )
     }
 
@@ -296,15 +443,12 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        let token = self
-            .tokens
-            .pop_front()
-            .ok_or_else(|| Error::Message("Reached end of input!".into()))?;
+        let token = self.tokens.pop_front().ok_or_else(end_of_input)?;
 
         visitor.visit_f64(
             token
-                .into_float()
-                .map_err(|t| Error::Message(format!("{t:?} is not a float")))?,
+                .into_float(self.autoconvert)
+                .map_err(|t| invalid_type(&t, "a float"))?,
         )
     }
 
@@ -312,18 +456,15 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        let token = self
-            .tokens
-            .pop_front()
-            .ok_or_else(|| Error::Message("Reached end of input!".into()))?;
+        let token = self.tokens.pop_front().ok_or_else(end_of_input)?;
 
         visitor.visit_char(
             token
                 .into_string()
-                .map_err(|t| Error::Message(format!("{t:?} is not a char")))?
+                .map_err(|t| invalid_type(&t, "a char"))?
                 .chars()
                 .next()
-                .ok_or_else(|| Error::Message("String is empty".into()))?,
+                .ok_or_else(|| Error::from(ErrorKind::Message("String is empty".into())))?,
         )
     }
 
@@ -331,15 +472,12 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        let token = self
-            .tokens
-            .pop_front()
-            .ok_or_else(|| Error::Message("Reached end of input!".into()))?;
+        let token = self.tokens.pop_front().ok_or_else(end_of_input)?;
 
         visitor.visit_str(
             token
                 .into_string()
-                .map_err(|t| Error::Message(format!("{t:?} is not a str")))?
+                .map_err(|t| invalid_type(&t, "a string"))?
                 .as_str(),
         )
     }
@@ -348,30 +486,44 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        let token = self
-            .tokens
-            .pop_front()
-            .ok_or_else(|| Error::Message("Reached end of input!".into()))?;
+        let token = self.tokens.pop_front().ok_or_else(end_of_input)?;
 
         visitor.visit_string(
             token
                 .into_string()
-                .map_err(|t| Error::Message(format!("{t:?} is not a str")))?,
+                .map_err(|t| invalid_type(&t, "a string"))?,
         )
     }
 
-    fn deserialize_bytes<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unimplemented!("")
+        self.deserialize_byte_buf(visitor)
     }
 
-    fn deserialize_byte_buf<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    /// Bytes are read from an array or list of ints, or from the UTF-8 of a
+    /// string.
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unimplemented!("")
+        let token = self.tokens.pop_front().ok_or_else(end_of_input)?;
+
+        let len = match token {
+            Token::String(s) => return visitor.visit_byte_buf(s.into_bytes()),
+            token => token.into_count().map_err(|t| invalid_type(&t, "bytes"))?,
+        };
+
+        let mut bytes = Vec::with_capacity(len);
+        for _ in 0..len {
+            let token = self.tokens.pop_front().ok_or_else(end_of_input)?;
+            let byte = token.into_int::<u8>(false, "a u8").map_err(|_| {
+                Error::from(ErrorKind::Message("Expected an int from 0 to 255".into()))
+            })?;
+            bytes.push(byte);
+        }
+        visitor.visit_byte_buf(bytes)
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -381,9 +533,9 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
         let len = self
             .tokens
             .pop_front()
-            .ok_or_else(|| Error::Message("Reached end of input!".into()))?
+            .ok_or_else(end_of_input)?
             .into_count()
-            .map_err(|t| Error::Message(format!("{t:?} is not a count")))?;
+            .map_err(|t| invalid_type(&t, "an array or list"))?;
 
         if len == 0 {
             visitor.visit_none()
@@ -399,14 +551,16 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
         let len = self
             .tokens
             .pop_front()
-            .ok_or_else(|| Error::Message("Reached end of input!".into()))?
+            .ok_or_else(end_of_input)?
             .into_count()
-            .map_err(|t| Error::Message(format!("{t:?} is not a count")))?;
+            .map_err(|t| invalid_type(&t, "an array or list"))?;
 
         if len == 0 {
             visitor.visit_unit()
         } else {
-            Err(Error::Message("Expected empty list".into()))
+            Err(Error::from(ErrorKind::Message(
+                "Expected empty list".into(),
+            )))
         }
     }
 
@@ -423,23 +577,28 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
 
     fn deserialize_newtype_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
+        if name == VALUE_TOKEN {
+            let outer = std::mem::replace(&mut self.in_value, true);
+            let res = visitor.visit_newtype_struct(&mut *self);
+            self.in_value = outer;
+            return res;
+        }
+
         let len = self
             .tokens
             .pop_front()
-            .ok_or_else(|| Error::Message("Reached end of input!".into()))?
+            .ok_or_else(end_of_input)?
             .into_count()
-            .map_err(|t| Error::Message(format!("{t:?} is not a count")))?;
+            .map_err(|t| invalid_type(&t, "an array or list"))?;
 
         if len != 1 {
-            return Err(Error::Message(format!(
-                "Expected 1 field in struct got {len}"
-            )));
+            return Err(ErrorKind::Message(format!("Expected 1 field in struct got {len}")).into());
         }
 
         visitor.visit_newtype_struct(self)
@@ -452,13 +611,14 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
         let count = self
             .tokens
             .pop_front()
-            .ok_or_else(|| Error::Message("Reached end of input!".into()))?
+            .ok_or_else(end_of_input)?
             .into_count()
-            .map_err(|t| Error::Message(format!("Expected field count, got {t:?}")))?;
+            .map_err(|t| invalid_type(&t, "a group, array or list"))?;
 
         visitor.visit_seq(SeqAccessor {
             de: self,
             remaining: count,
+            index: 0,
         })
     }
 
@@ -488,13 +648,14 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
         let count = self
             .tokens
             .pop_front()
-            .ok_or_else(|| Error::Message("Reached end of input!".into()))?
+            .ok_or_else(end_of_input)?
             .into_count()
-            .map_err(|t| Error::Message(format!("Expected field count, got {t:?}")))?;
+            .map_err(|t| invalid_type(&t, "a group, array or list"))?;
 
         visitor.visit_map(MapAccessor {
             de: self,
             remaining: count,
+            len: None,
         })
     }
 
@@ -510,13 +671,14 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
         let count = self
             .tokens
             .pop_front()
-            .ok_or_else(|| Error::Message("Reached end of input!".into()))?
+            .ok_or_else(end_of_input)?
             .into_count()
-            .map_err(|t| Error::Message(format!("Expected field count, got {t:?}")))?;
+            .map_err(|t| invalid_type(&t, "a group, array or list"))?;
 
         visitor.visit_map(StructAccessor {
             de: self,
             remaining: count,
+            len: None,
         })
     }
 
@@ -529,7 +691,7 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        if let Some(Token::SeqCount(_) | Token::MapCount(_)) = self.tokens.front() {
+        if let Some(Token::SeqCount(..) | Token::MapCount(_)) = self.tokens.front() {
             self.tokens.pop_front();
         };
 
@@ -543,9 +705,9 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
         let token = self
             .tokens
             .pop_front()
-            .ok_or_else(|| Error::Message("Reached end of input!".into()))?
+            .ok_or_else(end_of_input)?
             .into_string()
-            .map_err(|t| Error::Message(format!("{t:?} is not an identifier")))?;
+            .map_err(|t| invalid_type(&t, "an identifier"))?;
 
         visitor.visit_str(token.as_str())
     }
@@ -559,8 +721,9 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
 }
 
 struct SeqAccessor<'a, 'de: 'a> {
-    de: &'a mut Deserializer<'de>,
+    de: &'a mut ValueDeserializer<'de>,
     remaining: usize,
+    index: usize,
 }
 
 impl<'de, 'a> SeqAccess<'de> for SeqAccessor<'a, 'de> {
@@ -572,16 +735,76 @@ impl<'de, 'a> SeqAccess<'de> for SeqAccessor<'a, 'de> {
     {
         if self.remaining > 0 {
             self.remaining -= 1;
-            seed.deserialize(&mut *self.de).map(Some)
+            let len = self.de.enter(Segment::Index(self.index));
+            self.index += 1;
+            let res = seed.deserialize(&mut *self.de).map(Some);
+            self.de.leave(Some(len), res)
         } else {
             Ok(None)
         }
     }
 }
 
+/// An array or list inside a [`Value`], presented as a map from a key naming
+/// its type to its elements. An `int64` is marked the same way, so that it
+/// is not narrowed to an `int`.
+pub(super) struct MarkedSeq<D> {
+    de: Option<D>,
+    key: Option<&'static str>,
+}
+
+impl<D> MarkedSeq<D> {
+    pub(super) fn new(de: D, array_type: ArrayType) -> Self {
+        let key = match array_type {
+            ArrayType::Array => ARRAY_TOKEN,
+            ArrayType::List => LIST_TOKEN,
+        };
+        MarkedSeq {
+            de: Some(de),
+            key: Some(key),
+        }
+    }
+}
+
+impl MarkedSeq<I64Deserializer<Error>> {
+    pub(super) fn int64(v: i64) -> Self {
+        MarkedSeq {
+            de: Some(v.into_deserializer()),
+            key: Some(INT64_TOKEN),
+        }
+    }
+}
+
+impl<'de, D> MapAccess<'de> for MarkedSeq<D>
+where
+    D: de::Deserializer<'de, Error = Error>,
+{
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        self.key
+            .take()
+            .map(|key| seed.deserialize(key.into_deserializer()))
+            .transpose()
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let de = self.de.take().expect("value after key");
+        seed.deserialize(de)
+    }
+}
+
 struct StructAccessor<'a, 'de: 'a> {
-    de: &'a mut Deserializer<'de>,
+    de: &'a mut ValueDeserializer<'de>,
     remaining: usize,
+    /// The length of the path before the current setting
+    len: Option<usize>,
 }
 
 impl<'de, 'a> MapAccess<'de> for StructAccessor<'a, 'de> {
@@ -593,7 +816,12 @@ impl<'de, 'a> MapAccess<'de> for StructAccessor<'a, 'de> {
     {
         if self.remaining > 0 {
             self.remaining -= 1;
-            seed.deserialize(&mut *self.de).map(Some)
+            self.len = self.de.enter_key();
+            let res = seed.deserialize(&mut *self.de).map(Some);
+            if res.is_err() {
+                return self.de.leave(self.len.take(), res);
+            }
+            res
         } else {
             Ok(None)
         }
@@ -603,13 +831,16 @@ impl<'de, 'a> MapAccess<'de> for StructAccessor<'a, 'de> {
     where
         V: DeserializeSeed<'de>,
     {
-        seed.deserialize(&mut *self.de)
+        let res = seed.deserialize(&mut *self.de);
+        self.de.leave(self.len.take(), res)
     }
 }
 
 struct MapAccessor<'a, 'de: 'a> {
-    de: &'a mut Deserializer<'de>,
+    de: &'a mut ValueDeserializer<'de>,
     remaining: usize,
+    /// The length of the path before the current setting
+    len: Option<usize>,
 }
 
 impl<'de, 'a> MapAccess<'de> for MapAccessor<'a, 'de> {
@@ -622,10 +853,15 @@ impl<'de, 'a> MapAccess<'de> for MapAccessor<'a, 'de> {
         if self.remaining > 0 {
             self.remaining -= 1;
             // Consume inner tuple SeqCount when map entries are encoded as tuples
-            if let Some(Token::SeqCount(_)) = self.de.tokens.front() {
+            if let Some(Token::SeqCount(..)) = self.de.tokens.front() {
                 self.de.tokens.pop_front();
             }
-            seed.deserialize(&mut *self.de).map(Some)
+            self.len = self.de.enter_key();
+            let res = seed.deserialize(&mut *self.de).map(Some);
+            if res.is_err() {
+                return self.de.leave(self.len.take(), res);
+            }
+            res
         } else {
             Ok(None)
         }
@@ -635,17 +871,20 @@ impl<'de, 'a> MapAccess<'de> for MapAccessor<'a, 'de> {
     where
         V: DeserializeSeed<'de>,
     {
-        seed.deserialize(&mut *self.de)
+        let res = seed.deserialize(&mut *self.de);
+        self.de.leave(self.len.take(), res)
     }
 }
 
 struct Enum<'a, 'de: 'a> {
-    de: &'a mut Deserializer<'de>,
+    de: &'a mut ValueDeserializer<'de>,
+    /// The length of the path before the variant name
+    len: Option<usize>,
 }
 
 impl<'a, 'de> Enum<'a, 'de> {
-    fn new(de: &'a mut Deserializer<'de>) -> Self {
-        Enum { de }
+    fn new(de: &'a mut ValueDeserializer<'de>) -> Self {
+        Enum { de, len: None }
     }
 }
 
@@ -657,7 +896,10 @@ impl<'de, 'a> EnumAccess<'de> for Enum<'a, 'de> {
     where
         V: DeserializeSeed<'de>,
     {
-        Ok((seed.deserialize(&mut *self.de)?, self))
+        let key = self.de.next_key();
+        let variant = seed.deserialize(&mut *self.de)?;
+        let len = key.map(|key| self.de.enter(Segment::Key(&key)));
+        Ok((variant, Enum { len, ..self }))
     }
 }
 
@@ -665,7 +907,7 @@ impl<'de, 'a> VariantAccess<'de> for Enum<'a, 'de> {
     type Error = Error;
 
     fn unit_variant(self) -> Result<(), Self::Error> {
-        Ok(())
+        self.de.leave(self.len, Ok(()))
     }
 
     fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
@@ -673,14 +915,16 @@ impl<'de, 'a> VariantAccess<'de> for Enum<'a, 'de> {
         T: DeserializeSeed<'de>,
     {
         self.de.tokens.pop_front();
-        seed.deserialize(self.de)
+        let res = seed.deserialize(&mut *self.de);
+        self.de.leave(self.len, res)
     }
 
     fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        de::Deserializer::deserialize_seq(self.de, visitor)
+        let res = de::Deserializer::deserialize_seq(&mut *self.de, visitor);
+        self.de.leave(self.len, res)
     }
 
     fn struct_variant<V>(
@@ -691,6 +935,66 @@ impl<'de, 'a> VariantAccess<'de> for Enum<'a, 'de> {
     where
         V: Visitor<'de>,
     {
-        de::Deserializer::deserialize_struct(self.de, "", &[], visitor)
+        let res = de::Deserializer::deserialize_struct(&mut *self.de, "", &[], visitor);
+        self.de.leave(self.len, res)
+    }
+}
+
+macro_rules! forward_to_tokens {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                let mut de = ValueDeserializer::from_value(self, false);
+                de::Deserializer::$method(&mut de, $($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+/// Deserializes the value itself, the same way as [`from_value`].
+impl<'de> de::Deserializer<'de> for Value {
+    type Error = Error;
+
+    forward_to_tokens! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Value {
+    type Deserializer = Value;
+
+    fn into_deserializer(self) -> Value {
+        self
     }
 }
@@ -3,29 +3,210 @@ use std::fmt::Display;
 
 use serde::{de, ser};
 
+use crate::ParseError;
+
+/// An error from serializing or deserializing libconfig, with the path of the
+/// setting it concerns and, when deserializing from text, where that setting
+/// is in the input.
+#[derive(Debug)]
+pub struct Error(Box<Inner>);
+
 #[derive(Debug)]
-pub enum Error {
+struct Inner {
+    kind: ErrorKind,
+    path: String,
+    location: Option<(usize, usize)>,
+    placed: bool,
+}
+
+/// What went wrong in an [`Error`].
+#[derive(Debug)]
+pub enum ErrorKind {
+    /// Any other error, such as one raised by a `Serialize` or `Deserialize`
+    /// impl.
     Message(String),
+    /// The input could not be parsed.
+    Syntax(ParseError),
+    /// A setting has a different type than the one deserialized.
+    InvalidType { expected: String, found: String },
+    /// A setting has the right type but a value the type deserialized cannot
+    /// hold, such as an integer out of its range.
+    InvalidValue { expected: String, found: String },
+    /// A struct field is not set in its group.
+    MissingField(&'static str),
+    /// A group has a setting that is not a field of the struct.
+    UnknownField {
+        field: String,
+        expected: &'static [&'static str],
+    },
+    /// An enum value names no variant of the enum.
+    UnknownVariant {
+        variant: String,
+        expected: &'static [&'static str],
+    },
+}
+
+impl Error {
+    fn new(kind: ErrorKind) -> Self {
+        Error(Box::new(Inner {
+            kind,
+            path: String::new(),
+            location: None,
+            placed: false,
+        }))
+    }
+
+    /// Places an error that has not been placed yet at the setting `path`,
+    /// which starts at `location` in the input.
+    pub(crate) fn at(
+        mut self,
+        path: &str,
+        location: impl FnOnce() -> Option<(usize, usize)>,
+    ) -> Self {
+        if !self.0.placed {
+            self.0.path = path.into();
+            self.0.location = location();
+            self.0.placed = true;
+        }
+        self
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.0.kind
+    }
+
+    /// The path of the setting the error concerns, e.g.
+    /// `scene_manager.scenes[0].scene_id`. Empty for the root value.
+    pub fn path(&self) -> &str {
+        &self.0.path
+    }
+
+    /// The 1-based line and column of the setting the error concerns, when
+    /// deserializing from text. For a syntax error, where parsing failed.
+    pub fn location(&self) -> Option<(usize, usize)> {
+        self.0.location
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Error::new(kind)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        let location = Some((e.line(), e.column()));
+        let mut error = Error::new(ErrorKind::Syntax(e));
+        error.0.location = location;
+        error.0.placed = true;
+        error
+    }
 }
 
 impl de::Error for Error {
     fn custom<T: Display>(msg: T) -> Self {
-        Error::Message(msg.to_string())
+        Error::new(ErrorKind::Message(msg.to_string()))
+    }
+
+    fn invalid_type(unexp: de::Unexpected, exp: &dyn de::Expected) -> Self {
+        Error::new(ErrorKind::InvalidType {
+            expected: exp.to_string(),
+            found: unexp.to_string(),
+        })
+    }
+
+    fn invalid_value(unexp: de::Unexpected, exp: &dyn de::Expected) -> Self {
+        Error::new(ErrorKind::InvalidValue {
+            expected: exp.to_string(),
+            found: unexp.to_string(),
+        })
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Error::new(ErrorKind::MissingField(field))
+    }
+
+    fn unknown_field(field: &str, expected: &'static [&'static str]) -> Self {
+        Error::new(ErrorKind::UnknownField {
+            field: field.into(),
+            expected,
+        })
+    }
+
+    fn unknown_variant(variant: &str, expected: &'static [&'static str]) -> Self {
+        Error::new(ErrorKind::UnknownVariant {
+            variant: variant.into(),
+            expected,
+        })
     }
 }
 
 impl ser::Error for Error {
     fn custom<T: Display>(msg: T) -> Self {
-        Error::Message(msg.to_string())
+        Error::new(ErrorKind::Message(msg.to_string()))
     }
 }
 
-impl Display for Error {
+/// Lists names as "one of `a`, `b`", the way serde does.
+fn one_of(f: &mut fmt::Formatter, names: &[&str]) -> fmt::Result {
+    match names {
+        [] => write!(f, "there are none"),
+        [name] => write!(f, "expected `{name}`"),
+        names => {
+            let names = names.iter().map(|n| format!("`{n}`")).collect::<Vec<_>>();
+            write!(f, "expected one of {}", names.join(", "))
+        }
+    }
+}
+
+impl Display for ErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Error::Message(msg) => write!(f, "{}", msg),
+            ErrorKind::Message(msg) => write!(f, "{}", msg),
+            ErrorKind::Syntax(e) => write!(f, "{e}"),
+            ErrorKind::InvalidType { expected, found } => {
+                write!(f, "invalid type: {found}, expected {expected}")
+            }
+            ErrorKind::InvalidValue { expected, found } => {
+                write!(f, "invalid value: {found}, expected {expected}")
+            }
+            ErrorKind::MissingField(field) => write!(f, "missing field `{field}`"),
+            ErrorKind::UnknownField { field, expected } => {
+                write!(f, "unknown field `{field}`, ")?;
+                one_of(f, expected)
+            }
+            ErrorKind::UnknownVariant { variant, expected } => {
+                write!(f, "unknown variant `{variant}`, ")?;
+                one_of(f, expected)
+            }
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let e = &self.0;
+
+        // A syntax error shows its own location.
+        if let ErrorKind::Syntax(_) = e.kind {
+            return write!(f, "{}", e.kind);
+        }
+        if let Some((line, column)) = e.location {
+            write!(f, "line {line}, column {column}: ")?;
+        }
+        if !e.path.is_empty() {
+            write!(f, "\"{}\": ", e.path)?;
+        }
+        write!(f, "{}", e.kind)
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.0.kind {
+            ErrorKind::Syntax(e) => Some(e),
+            _ => None,
+        }
+    }
+}
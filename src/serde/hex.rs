@@ -0,0 +1,24 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Name of the newtype struct [`Hex`] serializes as, which the libconfig
+/// serializer recognises.
+pub(crate) const HEX_TOKEN: &str = "$__libconfig_rs_private_Hex";
+
+/// Serializes the integers inside it in hexadecimal, like libconfig's
+/// `CONFIG_FORMAT_HEX`. Other serializers see only the inner value.
+///
+/// Deserializes from an integer written in any radix.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Hex<T>(pub T);
+
+impl<T: Serialize> Serialize for Hex<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(HEX_TOKEN, &self.0)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Hex<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Hex)
+    }
+}
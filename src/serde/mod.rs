@@ -1,3 +1,7 @@
 pub mod deserialize;
 pub mod error;
+pub mod hex;
 pub mod serialize;
+pub mod stream;
+pub mod to_value;
+pub mod value;
@@ -1,34 +1,214 @@
-use super::error::Error;
+use super::{error::Error, hex::HEX_TOKEN, value::INT64_TOKEN};
+use crate::{ArrayType, FormatOptions, IntFormat, printer};
 use serde::{Serialize, ser};
 
+/// An array or list being written. Its elements are collected so it can be
+/// laid out once its width is known.
+#[derive(Clone)]
+struct Seq {
+    start: usize,
+    column: usize,
+    /// `Array` if this should be written as an array when its elements allow
+    array_type: ArrayType,
+    /// Where each element starts in the output
+    elements: Vec<usize>,
+    /// The type of each element that is a scalar
+    scalars: Vec<Option<&'static str>>,
+}
+
 #[derive(Clone)]
 pub struct Serializer {
     output: String,
-    indent: usize,
+    /// Nesting depth of the settings currently being written
+    depth: usize,
     /// Stack tracking whether each struct/map level emitted braces
     braces_stack: Vec<bool>,
+    /// The arrays and lists being written, innermost last
+    seqs: Vec<Seq>,
+    /// The libconfig type of the last scalar written
+    scalar: Option<&'static str>,
+    /// Radix for integers, set while serializing the inside of a `Hex`
+    int_format: IntFormat,
+    /// Set while serializing an `int64` from a `Value`
+    int64: bool,
+    options: FormatOptions,
 }
 
 impl Serializer {
     fn write_indent(&mut self) {
-        for _ in 0..self.indent {
-            self.output.push(' ');
+        printer::indent(&mut self.output, self.depth, &self.options);
+    }
+
+    /// Writes the value of a setting whose name has just been written. The
+    /// assignment goes first, so that arrays are fitted at the column they
+    /// end up at, and is replaced if the value turns out to be a group.
+    fn write_setting<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let assignment_start = self.output.len();
+        printer::print_assignment(&mut self.output, false, self.depth, &self.options);
+        let start = self.output.len();
+        value.serialize(&mut *self)?;
+        if self.output[start..].starts_with('{') {
+            let mut assignment = String::new();
+            printer::print_assignment(&mut assignment, true, self.depth, &self.options);
+            self.output
+                .replace_range(assignment_start..start, &assignment);
         }
+        self.end_setting();
+        Ok(())
+    }
+
+    /// Ends a setting with the configured terminator and a line break.
+    fn end_setting(&mut self) {
+        self.output += self.options.terminator.as_str();
+        self.output.push('\n');
+    }
+
+    /// Opens a group whose settings are written one level deeper.
+    fn open_group(&mut self) {
+        self.output += "{\n";
+        self.depth += 1;
+    }
+
+    fn close_group(&mut self) {
+        self.depth -= 1;
+        self.write_indent();
+        self.output += "}";
+    }
+
+    /// Starts an array or list, whose elements are written one level deeper.
+    /// A sequence meant to be an array is still written as a list unless its
+    /// elements are scalars of one type, as libconfig requires.
+    fn open_seq(&mut self, array_type: ArrayType) {
+        let start = self.output.len();
+        let is_element = self
+            .seqs
+            .last()
+            .is_some_and(|seq| seq.elements.last() == Some(&start));
+        // Like the printer, elements are fitted as if at the start of their
+        // own line, which is where they go if the outer one is broken up.
+        let column = if is_element {
+            printer::indent_width(self.depth, &self.options)
+        } else {
+            let line_start = self.output.rfind('\n').map_or(0, |i| i + 1);
+            printer::line_width(&self.output[line_start..])
+        };
+        self.seqs.push(Seq {
+            start,
+            column,
+            array_type,
+            elements: Vec::new(),
+            scalars: Vec::new(),
+        });
+        self.depth += 1;
+    }
+
+    fn write_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let start = self.output.len();
+        if let Some(seq) = self.seqs.last_mut() {
+            seq.elements.push(start);
+        }
+        self.scalar = None;
+        value.serialize(&mut *self)?;
+        let scalar = match self.output[start..].chars().next() {
+            Some('{' | '[' | '(') => None,
+            _ => self.scalar,
+        };
+        if let Some(seq) = self.seqs.last_mut() {
+            seq.scalars.push(scalar);
+        }
+        Ok(())
+    }
+
+    /// Replaces the elements written since [`Serializer::open_seq`] with the
+    /// laid out array or list.
+    fn close_seq(&mut self) {
+        let Some(seq) = self.seqs.pop() else {
+            return;
+        };
+        self.depth -= 1;
+        let ends = seq
+            .elements
+            .iter()
+            .skip(1)
+            .copied()
+            .chain([self.output.len()]);
+        let elements = seq
+            .elements
+            .iter()
+            .zip(ends)
+            .map(|(&start, end)| self.output[start..end].to_string())
+            .collect::<Vec<_>>();
+        let homogeneous = match seq.scalars.split_first() {
+            Some((first, rest)) => first.is_some() && rest.iter().all(|s| s == first),
+            None => true,
+        };
+        let delimiters = match seq.array_type {
+            ArrayType::Array if homogeneous => ('[', ']'),
+            _ => ('(', ')'),
+        };
+        self.output.truncate(seq.start);
+        printer::print_elements(
+            &mut self.output,
+            &elements,
+            delimiters,
+            self.depth,
+            seq.column,
+            &self.options,
+        );
+    }
+
+    /// Writes an integer, as an `int64` only when it does not fit in 32 bits
+    /// or came from an `int64`, so that it reads back as the same kind of
+    /// setting. libconfig has no unsigned 64-bit integers, so a `u64` above
+    /// `i64::MAX` is an error.
+    fn write_int(&mut self, v: i128) -> Result<(), Error> {
+        if !self.int64 && i32::try_from(v).is_ok() {
+            printer::print_int(&mut self.output, v, 32, self.int_format);
+            self.scalar = Some("int");
+        } else if i64::try_from(v).is_ok() {
+            printer::print_int(&mut self.output, v, 64, self.int_format);
+            self.output += "L";
+            self.scalar = Some("int64");
+        } else {
+            return Err(ser::Error::custom(format!("{v} does not fit in an int64")));
+        }
+        Ok(())
     }
 }
 
 pub fn to_string<T>(value: &T) -> Result<String, Error>
+where
+    T: Serialize,
+{
+    to_string_with_options(value, &FormatOptions::default())
+}
+
+/// Like [`to_string`], with control over the layout.
+pub fn to_string_with_options<T>(value: &T, options: &FormatOptions) -> Result<String, Error>
 where
     T: Serialize,
 {
     let mut serializer = Serializer {
         output: String::new(),
-        indent: 0,
+        depth: 0,
         braces_stack: Vec::new(),
+        seqs: Vec::new(),
+        scalar: None,
+        int_format: IntFormat::Decimal,
+        int64: false,
+        options: options.clone(),
     };
-    serializer.output += "config : ";
-    value.serialize(&mut serializer)?;
-    serializer.output += ";\n";
+    serializer.output += "config";
+    serializer.write_setting(value)?;
+    if !options.trailing_newline {
+        serializer.output.pop();
+    }
     Ok(serializer.output)
 }
 
@@ -46,6 +226,7 @@ impl ser::Serializer for &mut Serializer {
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
         self.output += if v { "true" } else { "false" };
+        self.scalar = Some("bool");
         Ok(())
     }
 
@@ -58,14 +239,11 @@ impl ser::Serializer for &mut Serializer {
     }
 
     fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
-        self.output += &v.to_string();
-        Ok(())
+        self.write_int(v.into())
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
-        self.output += &v.to_string();
-        self.output += "L";
-        Ok(())
+        self.write_int(v.into())
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
@@ -77,23 +255,22 @@ impl ser::Serializer for &mut Serializer {
     }
 
     fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
-        self.output += &v.to_string();
-        Ok(())
+        self.write_int(v.into())
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
-        self.output += &v.to_string();
-        self.output += "L";
-        Ok(())
+        self.write_int(v.into())
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
-        self.output += &format!("{v:?}");
+        printer::print_float(&mut self.output, v, &self.options);
+        self.scalar = Some("float");
         Ok(())
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
-        self.output += &format!("{v:?}");
+        printer::print_float(&mut self.output, v, &self.options);
+        self.scalar = Some("float");
         Ok(())
     }
 
@@ -102,23 +279,29 @@ impl ser::Serializer for &mut Serializer {
     }
 
     fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
-        self.output.push('"');
-        for c in v.chars() {
-            match c {
-                '\\' => self.output.push_str("\\\\"),
-                '"' => self.output.push_str("\\\""),
-                '\n' => self.output.push_str("\\n"),
-                '\r' => self.output.push_str("\\r"),
-                '\t' => self.output.push_str("\\t"),
-                _ => self.output.push(c),
+        for (i, piece) in printer::split_string(v, &self.options)
+            .into_iter()
+            .enumerate()
+        {
+            if i > 0 {
+                // Continuation literals line up one level in from the setting.
+                self.output.push('\n');
+                printer::indent(&mut self.output, self.depth + 1, &self.options);
             }
+            printer::print_str(&mut self.output, piece, self.options.escapes);
         }
-        self.output.push('"');
+        self.scalar = Some("string");
         Ok(())
     }
 
-    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok, Self::Error> {
-        unimplemented!()
+    /// Bytes are written as an array of ints.
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        self.open_seq(ArrayType::Array);
+        for b in v {
+            self.write_element(b)?;
+        }
+        self.close_seq();
+        Ok(())
     }
 
     fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
@@ -130,9 +313,9 @@ impl ser::Serializer for &mut Serializer {
     where
         T: Serialize + ?Sized,
     {
-        self.output += "[ ";
-        value.serialize(&mut *self)?;
-        self.output += " ]";
+        self.open_seq(ArrayType::Array);
+        self.write_element(value)?;
+        self.close_seq();
         Ok(())
     }
 
@@ -152,19 +335,34 @@ impl ser::Serializer for &mut Serializer {
         _variant_index: u32,
         variant: &'static str,
     ) -> Result<Self::Ok, Self::Error> {
-        self.output += &format!("\"{variant}\"");
-        Ok(())
+        self.serialize_str(variant)
     }
 
     fn serialize_newtype_struct<T>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: Serialize + ?Sized,
     {
-        value.serialize(self)
+        if name == HEX_TOKEN {
+            let outer = std::mem::replace(&mut self.int_format, IntFormat::Hex);
+            let res = value.serialize(&mut *self);
+            self.int_format = outer;
+            return res;
+        }
+        if name == INT64_TOKEN {
+            self.int64 = true;
+            let res = value.serialize(&mut *self);
+            self.int64 = false;
+            return res;
+        }
+        // Read back by `deserialize_newtype_struct` as a list of one.
+        self.open_seq(ArrayType::List);
+        self.write_element(value)?;
+        self.close_seq();
+        Ok(())
     }
 
     fn serialize_newtype_variant<T>(
@@ -177,26 +375,25 @@ impl ser::Serializer for &mut Serializer {
     where
         T: Serialize + ?Sized,
     {
-        self.output += "{\n";
-        self.indent += 4;
+        self.open_group();
         self.write_indent();
         self.output += variant;
-        self.output += " : ( ";
-        value.serialize(&mut *self)?;
-        self.output += " );\n";
-        self.indent -= 4;
-        self.write_indent();
-        self.output += "}";
+        printer::print_assignment(&mut self.output, false, self.depth, &self.options);
+        self.open_seq(ArrayType::List);
+        self.write_element(value)?;
+        self.close_seq();
+        self.end_setting();
+        self.close_group();
         Ok(())
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
-        self.output += "( ";
+        self.open_seq(ArrayType::List);
         Ok(self)
     }
 
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
-        self.output += "[ ";
+        self.open_seq(ArrayType::Array);
         Ok(self)
     }
 
@@ -205,22 +402,27 @@ impl ser::Serializer for &mut Serializer {
         _name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleStruct, Self::Error> {
-        unimplemented!()
+        self.open_seq(ArrayType::Array);
+        Ok(self)
     }
 
     fn serialize_tuple_variant(
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
-        unimplemented!()
+        self.open_group();
+        self.write_indent();
+        self.output += variant;
+        printer::print_assignment(&mut self.output, false, self.depth, &self.options);
+        self.open_seq(ArrayType::Array);
+        Ok(self)
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
-        self.output += "{\n";
-        self.indent += 4;
+        self.open_group();
         self.braces_stack.push(true);
         Ok(self)
     }
@@ -230,8 +432,7 @@ impl ser::Serializer for &mut Serializer {
         _name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
-        self.output += "{\n";
-        self.indent += 4;
+        self.open_group();
         self.braces_stack.push(true);
         Ok(self)
     }
@@ -243,12 +444,11 @@ impl ser::Serializer for &mut Serializer {
         variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
-        self.output += "{\n";
-        self.indent += 4;
+        self.open_group();
         self.write_indent();
         self.output += variant;
-        self.output += " : {\n";
-        self.indent += 4;
+        printer::print_assignment(&mut self.output, true, self.depth, &self.options);
+        self.open_group();
         self.braces_stack.push(true);
         self.braces_stack.push(true);
         Ok(self)
@@ -263,15 +463,11 @@ impl ser::SerializeSeq for &mut Serializer {
     where
         T: Serialize + ?Sized,
     {
-        if !self.output.ends_with("( ") {
-            self.output += ", ";
-        }
-        value.serialize(&mut **self)?;
-        Ok(())
+        self.write_element(value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        self.output += " )";
+        self.close_seq();
         Ok(())
     }
 }
@@ -284,15 +480,11 @@ impl ser::SerializeTuple for &mut Serializer {
     where
         T: Serialize + ?Sized,
     {
-        if !self.output.ends_with("[ ") {
-            self.output += ", ";
-        }
-        value.serialize(&mut **self)?;
-        Ok(())
+        self.write_element(value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        self.output += " ]";
+        self.close_seq();
         Ok(())
     }
 }
@@ -301,15 +493,16 @@ impl ser::SerializeTupleStruct for &mut Serializer {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T>(&mut self, _value: &T) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
         T: Serialize + ?Sized,
     {
-        unimplemented!()
+        self.write_element(value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        unimplemented!()
+        self.close_seq();
+        Ok(())
     }
 }
 
@@ -317,15 +510,18 @@ impl ser::SerializeTupleVariant for &mut Serializer {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T>(&mut self, _value: &T) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
         T: Serialize + ?Sized,
     {
-        unimplemented!()
+        self.write_element(value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        unimplemented!()
+        self.close_seq();
+        self.end_setting();
+        self.close_group();
+        Ok(())
     }
 }
 
@@ -346,7 +542,6 @@ impl ser::SerializeMap for &mut Serializer {
             self.output.truncate(start);
             self.output += &key_part[1..key_part.len() - 1];
         }
-        self.output += " : ";
         Ok(())
     }
 
@@ -354,16 +549,12 @@ impl ser::SerializeMap for &mut Serializer {
     where
         T: Serialize + ?Sized,
     {
-        value.serialize(&mut **self)?;
-        self.output += ";\n";
-        Ok(())
+        self.write_setting(value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
         if let Some(true) = self.braces_stack.pop() {
-            self.indent -= 4;
-            self.write_indent();
-            self.output += "}";
+            self.close_group();
         }
         Ok(())
     }
@@ -379,17 +570,12 @@ impl ser::SerializeStruct for &mut Serializer {
     {
         self.write_indent();
         self.output += key;
-        self.output += " : ";
-        value.serialize(&mut **self)?;
-        self.output += ";\n";
-        Ok(())
+        self.write_setting(value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
         if let Some(true) = self.braces_stack.pop() {
-            self.indent -= 4;
-            self.write_indent();
-            self.output += "}";
+            self.close_group();
         }
         Ok(())
     }
@@ -405,25 +591,18 @@ impl ser::SerializeStructVariant for &mut Serializer {
     {
         self.write_indent();
         self.output += key;
-        self.output += " : ";
-        value.serialize(&mut **self)?;
-        self.output += ";\n";
-        Ok(())
+        self.write_setting(value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
         // Close inner struct brace
         if let Some(true) = self.braces_stack.pop() {
-            self.indent -= 4;
-            self.write_indent();
-            self.output += "}";
+            self.close_group();
         }
         // Close outer variant wrapper brace
         if let Some(true) = self.braces_stack.pop() {
-            self.output += ";\n";
-            self.indent -= 4;
-            self.write_indent();
-            self.output += "}";
+            self.end_setting();
+            self.close_group();
         }
         Ok(())
     }
@@ -0,0 +1,836 @@
+use super::{
+    deserialize::{MarkedSeq, ValueDeserializer, enter},
+    error::{Error, ErrorKind},
+    value::VALUE_TOKEN,
+};
+use crate::{
+    ArrayType, ParseOptions, Value,
+    error::line_column,
+    parser::{self, Kind, Reader, Scalar, Setting},
+    path::Segment,
+};
+use serde::de::{
+    self, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, Unexpected,
+    VariantAccess, Visitor, value::BorrowedStrDeserializer,
+};
+use std::fmt::Display;
+
+/// Deserializes straight from libconfig text, without building a [`Value`]
+/// first. Strings without escapes are borrowed from the input, so fields
+/// may be `&'de str`.
+///
+/// Like [`from_str`](crate::from_str), the input is a single setting such as
+/// `config : { ... };`, and its value is what is deserialized. Call
+/// [`Deserializer::end`] afterwards to check that nothing follows it.
+pub struct Deserializer<'de> {
+    reader: Reader<'de>,
+    autoconvert: bool,
+    /// Set while deserializing a [`Value`], to pass on the type of arrays
+    in_value: bool,
+    /// The path of the setting being deserialized, for errors
+    path: String,
+    /// Where the value of the root setting starts
+    start: usize,
+}
+
+impl<'de> Deserializer<'de> {
+    /// Reads `input` up to the value of its root setting.
+    pub fn new(input: &'de str) -> Result<Self, Error> {
+        Self::with_options(input, &ParseOptions::default())
+    }
+
+    /// Like [`Deserializer::new`], with control over how the input is read.
+    /// Settings are read as they are deserialized, so a duplicate setting is
+    /// always an error, whatever the policy in `options`.
+    pub fn with_options(input: &'de str, options: &ParseOptions) -> Result<Self, Error> {
+        let mut reader = Reader::new(parser::Context::new(options), input);
+        reader.root()?;
+        let start = reader.offset()?;
+
+        Ok(Deserializer {
+            reader,
+            autoconvert: options.autoconvert,
+            in_value: false,
+            path: String::new(),
+            start,
+        })
+    }
+
+    /// Checks that nothing but whitespace and comments follows the root
+    /// setting.
+    pub fn end(&mut self) -> Result<(), Error> {
+        Ok(self.reader.end()?)
+    }
+
+    /// Places an error from deserializing the root value.
+    pub(super) fn place_root(&self, e: Error) -> Error {
+        self.place(e, Some(self.start))
+    }
+
+    fn place(&self, e: Error, start: Option<usize>) -> Error {
+        e.at(&self.path, || {
+            start.map(|start| line_column(self.reader.input(), start))
+        })
+    }
+
+    fn enter(&mut self, segment: Segment) -> usize {
+        enter(&mut self.path, segment)
+    }
+
+    /// Places `res`, if it failed, at the current path, whose value starts at
+    /// byte `start`, and restores the path to length `len`.
+    fn leave<T>(
+        &mut self,
+        len: usize,
+        start: Option<usize>,
+        res: Result<T, Error>,
+    ) -> Result<T, Error> {
+        let res = res.map_err(|e| self.place(e, start));
+        self.path.truncate(len);
+        res
+    }
+
+    /// The next value, which should be a scalar.
+    fn scalar(&mut self, expected: &str) -> Result<Scalar<'de>, Error> {
+        match self.reader.peek()? {
+            Kind::Scalar => Ok(self.reader.scalar()?),
+            kind => Err(invalid_type(describe_kind(kind), expected)),
+        }
+    }
+
+    /// The next value as the integer type `T` named by `expected`, if it is
+    /// in range.
+    fn int<T: TryFrom<i128>>(&mut self, expected: &str) -> Result<T, Error> {
+        let scalar = self.scalar("an integer")?;
+        let v = match scalar {
+            Scalar::Value(Value::Int(v, _)) => i128::from(v),
+            Scalar::Value(Value::Int64(v, _)) => i128::from(v),
+            Scalar::Value(Value::UInt64(v, _)) => i128::from(v),
+            Scalar::Value(Value::Float(v)) if self.autoconvert => v as i128,
+            _ => return Err(invalid_type(describe(&scalar), "an integer")),
+        };
+        T::try_from(v).map_err(|_| invalid_value(describe(&scalar), expected))
+    }
+
+    fn float(&mut self) -> Result<f64, Error> {
+        match self.scalar("a float")? {
+            Scalar::Value(Value::Float(v)) => Ok(v),
+            Scalar::Value(Value::Int(v, _)) if self.autoconvert => Ok(v.into()),
+            Scalar::Value(Value::Int64(v, _)) if self.autoconvert => Ok(v as f64),
+            Scalar::Value(Value::UInt64(v, _)) if self.autoconvert => Ok(v as f64),
+            found => Err(invalid_type(describe(&found), "a float")),
+        }
+    }
+
+    /// Moves into the array or list holding a newtype or `Some` value.
+    fn open_wrapper(&mut self) -> Result<bool, Error> {
+        match self.reader.peek()? {
+            kind @ (Kind::Array | Kind::List) => {
+                self.reader.open(kind);
+                Ok(self.reader.next_element()?)
+            }
+            kind => Err(invalid_type(describe_kind(kind), "an array or list")),
+        }
+    }
+}
+
+/// Describes a scalar the way serde's `Unexpected` does.
+fn describe(scalar: &Scalar) -> String {
+    match scalar {
+        Scalar::Str(v) => format!("string {v:?}"),
+        Scalar::Value(Value::String(v)) => format!("string {v:?}"),
+        Scalar::Value(Value::Bool(v)) => format!("bool `{v}`"),
+        Scalar::Value(Value::Int(v, _)) => format!("int `{v}`"),
+        Scalar::Value(Value::Int64(v, _)) => format!("int `{v}`"),
+        Scalar::Value(Value::UInt64(v, _)) => format!("int `{v}`"),
+        Scalar::Value(Value::Float(v)) => format!("float `{v}`"),
+        Scalar::Value(v) => v.type_name().into(),
+    }
+}
+
+fn describe_kind(kind: Kind) -> &'static str {
+    match kind {
+        Kind::Group => "group",
+        Kind::Array => "array",
+        Kind::List => "list",
+        Kind::Scalar => "value",
+    }
+}
+
+fn invalid_type(found: impl Display, expected: &str) -> Error {
+    ErrorKind::InvalidType {
+        expected: expected.into(),
+        found: found.to_string(),
+    }
+    .into()
+}
+
+fn invalid_value(found: impl Display, expected: &str) -> Error {
+    ErrorKind::InvalidValue {
+        expected: expected.into(),
+        found: found.to_string(),
+    }
+    .into()
+}
+
+fn message(msg: impl Into<String>) -> Error {
+    ErrorKind::Message(msg.into()).into()
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.reader.peek()? {
+            Kind::Group => self.deserialize_map(visitor),
+            Kind::Array if self.in_value => {
+                visitor.visit_map(MarkedSeq::new(self, ArrayType::Array))
+            }
+            Kind::List if self.in_value => visitor.visit_map(MarkedSeq::new(self, ArrayType::List)),
+            Kind::Array | Kind::List => self.deserialize_seq(visitor),
+            Kind::Scalar => match self.reader.scalar()? {
+                Scalar::Str(v) => visitor.visit_borrowed_str(v),
+                Scalar::Value(Value::String(v)) => visitor.visit_string(v),
+                Scalar::Value(Value::Bool(v)) => visitor.visit_bool(v),
+                Scalar::Value(Value::Int(v, _)) => visitor.visit_i64(v.into()),
+                Scalar::Value(Value::Int64(v, _)) if self.in_value => {
+                    visitor.visit_map(MarkedSeq::int64(v))
+                }
+                Scalar::Value(Value::Int64(v, _)) => visitor.visit_i64(v),
+                Scalar::Value(Value::UInt64(v, _)) => visitor.visit_u64(v),
+                Scalar::Value(Value::Float(v)) => visitor.visit_f64(v),
+                Scalar::Value(v) => Err(invalid_type(v.type_name(), "a value")),
+            },
+        }
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.scalar("a bool")? {
+            Scalar::Value(Value::Bool(v)) => visitor.visit_bool(v),
+            found => Err(invalid_type(describe(&found), "a bool")),
+        }
+    }
+
+    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_i8(self.int("an i8")?)
+    }
+
+    fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_i16(self.int("an i16")?)
+    }
+
+    fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_i32(self.int("an i32")?)
+    }
+
+    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_i64(self.int("an i64")?)
+    }
+
+    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_u8(self.int("a u8")?)
+    }
+
+    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_u16(self.int("a u16")?)
+    }
+
+    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_u32(self.int("a u32")?)
+    }
+
+    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_u64(self.int("a u64")?)
+    }
+
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_f32(self.float()? as f32)
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_f64(self.float()?)
+    }
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let c = match self.scalar("a char")? {
+            Scalar::Str(v) => v.chars().next(),
+            Scalar::Value(Value::String(v)) => v.chars().next(),
+            found => return Err(invalid_type(describe(&found), "a char")),
+        };
+        visitor.visit_char(c.ok_or_else(|| message("String is empty"))?)
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.scalar("a string")? {
+            Scalar::Str(v) => visitor.visit_borrowed_str(v),
+            Scalar::Value(Value::String(v)) => visitor.visit_string(v),
+            found => Err(invalid_type(describe(&found), "a string")),
+        }
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.reader.peek()? != Kind::Scalar {
+            return self.deserialize_byte_buf(visitor);
+        }
+        match self.reader.scalar()? {
+            Scalar::Str(v) => visitor.visit_borrowed_bytes(v.as_bytes()),
+            Scalar::Value(Value::String(v)) => visitor.visit_byte_buf(v.into_bytes()),
+            found => Err(invalid_type(describe(&found), "bytes")),
+        }
+    }
+
+    /// Bytes are read from an array or list of ints, or from the UTF-8 of a
+    /// string.
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let kind = self.reader.peek()?;
+        if let Kind::Group = kind {
+            return Err(invalid_type(describe_kind(kind), "bytes"));
+        }
+        if let Kind::Scalar = kind {
+            return match self.reader.scalar()? {
+                Scalar::Str(v) => visitor.visit_byte_buf(v.as_bytes().to_vec()),
+                Scalar::Value(Value::String(v)) => visitor.visit_byte_buf(v.into_bytes()),
+                found => Err(invalid_type(describe(&found), "bytes")),
+            };
+        }
+
+        self.reader.open(kind);
+        let mut bytes = Vec::new();
+        while self.reader.next_element()? {
+            let byte = match self.reader.scalar()? {
+                Scalar::Value(Value::Int(v, _)) => u8::try_from(v).ok(),
+                Scalar::Value(Value::Int64(v, _)) => u8::try_from(v).ok(),
+                _ => None,
+            };
+            bytes.push(byte.ok_or_else(|| message("Expected an int from 0 to 255"))?);
+        }
+        visitor.visit_byte_buf(bytes)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if !self.open_wrapper()? {
+            return visitor.visit_none();
+        }
+        let value = visitor.visit_some(&mut *self)?;
+        self.reader.close()?;
+        Ok(value)
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.open_wrapper()? {
+            return Err(message("Expected empty list"));
+        }
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if name == VALUE_TOKEN {
+            let outer = std::mem::replace(&mut self.in_value, true);
+            let res = visitor.visit_newtype_struct(&mut *self);
+            self.in_value = outer;
+            return res;
+        }
+
+        if !self.open_wrapper()? {
+            return Err(message("Expected 1 field in struct got 0"));
+        }
+        let value = visitor.visit_newtype_struct(&mut *self)?;
+
+        let mut len = 1;
+        while self.reader.next_element()? {
+            self.reader.skip()?;
+            len += 1;
+        }
+        if len != 1 {
+            return Err(message(format!("Expected 1 field in struct got {len}")));
+        }
+        Ok(value)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let kind = self.reader.peek()?;
+        if !matches!(kind, Kind::Array | Kind::List) {
+            return Err(invalid_type(describe_kind(kind), "an array or list"));
+        }
+
+        self.reader.open(kind);
+        let mut elements = Elements {
+            de: self,
+            index: 0,
+            done: false,
+        };
+        let value = visitor.visit_seq(&mut elements)?;
+        elements.finish()?;
+        Ok(value)
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    /// A map is read from a group, or from an array or list of key and value
+    /// pairs.
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.reader.peek()? {
+            Kind::Group => self.deserialize_struct("", &[], visitor),
+            kind @ (Kind::Array | Kind::List) => {
+                self.reader.open(kind);
+                let mut entries = Entries {
+                    de: self,
+                    done: false,
+                };
+                let value = visitor.visit_map(&mut entries)?;
+                if !entries.done {
+                    entries.de.reader.close()?;
+                }
+                Ok(value)
+            }
+            kind => Err(invalid_type(describe_kind(kind), "a group")),
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let kind = self.reader.peek()?;
+        if kind != Kind::Group {
+            return Err(invalid_type(describe_kind(kind), "a group"));
+        }
+
+        self.reader.open(kind);
+        let mut settings = Settings {
+            de: self,
+            included: None,
+            pending: None,
+            current: None,
+            done: false,
+        };
+        let value = visitor.visit_map(&mut settings)?;
+        if !settings.done {
+            settings.de.reader.close()?;
+        }
+        Ok(value)
+    }
+
+    /// A unit variant is read from a string, any other from a group with a
+    /// single setting named after the variant.
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let grouped = match self.reader.peek()? {
+            Kind::Scalar => false,
+            Kind::Group => {
+                self.reader.open(Kind::Group);
+                true
+            }
+            kind => {
+                return Err(invalid_type(
+                    describe_kind(kind),
+                    "a string or group naming a variant",
+                ));
+            }
+        };
+
+        visitor.visit_enum(Variant {
+            de: self,
+            grouped,
+            current: None,
+        })
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.reader.skip()?;
+        visitor.visit_unit()
+    }
+}
+
+struct Elements<'a, 'de: 'a> {
+    de: &'a mut Deserializer<'de>,
+    index: usize,
+    done: bool,
+}
+
+impl Elements<'_, '_> {
+    /// Reads past any elements the visitor left.
+    fn finish(self) -> Result<(), Error> {
+        if !self.done {
+            self.de.reader.close()?;
+        }
+        Ok(())
+    }
+}
+
+impl<'de, 'a> SeqAccess<'de> for Elements<'a, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.done || !self.de.reader.next_element()? {
+            self.done = true;
+            return Ok(None);
+        }
+
+        let len = self.de.enter(Segment::Index(self.index));
+        self.index += 1;
+        let start = self.de.reader.offset()?;
+        let res = seed.deserialize(&mut *self.de);
+        self.de.leave(len, Some(start), res).map(Some)
+    }
+}
+
+/// The settings of a group, including those of files it `@include`s.
+struct Settings<'a, 'de: 'a> {
+    de: &'a mut Deserializer<'de>,
+    included: Option<indexmap::map::IntoIter<String, Value>>,
+    /// The value of the current setting, when it is from an included file
+    pending: Option<Value>,
+    /// The length of the path before the current setting, and where its value
+    /// starts in the input
+    current: Option<(usize, Option<usize>)>,
+    done: bool,
+}
+
+impl<'a, 'de> Settings<'a, 'de> {
+    fn key<T>(&mut self, res: Result<T, Error>) -> Result<Option<T>, Error> {
+        match res {
+            Ok(key) => Ok(Some(key)),
+            Err(e) => {
+                let (len, start) = self.current.take().expect("inside a setting");
+                self.de.leave(len, start, Err(e))
+            }
+        }
+    }
+}
+
+impl<'de, 'a> MapAccess<'de> for Settings<'a, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        loop {
+            if let Some((name, value)) = self.included.as_mut().and_then(Iterator::next) {
+                let len = self.de.enter(Segment::Key(&name));
+                self.current = Some((len, None));
+                self.pending = Some(value);
+                let res = seed.deserialize(IntoDeserializer::<Error>::into_deserializer(name));
+                return self.key(res);
+            }
+            self.included = None;
+            if self.done {
+                return Ok(None);
+            }
+
+            match self.de.reader.next_setting()? {
+                None => {
+                    self.done = true;
+                    return Ok(None);
+                }
+                Some(Setting::Included(settings)) => self.included = Some(settings.into_iter()),
+                Some(Setting::Name(name)) => {
+                    let len = self.de.enter(Segment::Key(name));
+                    self.current = Some((len, Some(self.de.reader.offset()?)));
+                    let res = seed.deserialize(BorrowedStrDeserializer::<Error>::new(name));
+                    return self.key(res);
+                }
+            }
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let (len, start) = self.current.take().expect("value after key");
+        let res = match self.pending.take() {
+            Some(value) => {
+                let de = &self.de;
+                let mut de =
+                    ValueDeserializer::nested(value, de.autoconvert, de.in_value, &de.path);
+                seed.deserialize(&mut de).map_err(|e| de.place(e))
+            }
+            None => seed.deserialize(&mut *self.de).and_then(|value| {
+                self.de.reader.end_setting()?;
+                Ok(value)
+            }),
+        };
+        self.de.leave(len, start, res)
+    }
+}
+
+/// The entries of a map written as an array or list of `(key, value)` pairs.
+struct Entries<'a, 'de: 'a> {
+    de: &'a mut Deserializer<'de>,
+    done: bool,
+}
+
+impl<'de, 'a> MapAccess<'de> for Entries<'a, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.done || !self.de.reader.next_element()? {
+            self.done = true;
+            return Ok(None);
+        }
+
+        match self.de.reader.peek()? {
+            kind @ (Kind::Array | Kind::List) => self.de.reader.open(kind),
+            kind => return Err(invalid_type(describe_kind(kind), "a key and value pair")),
+        }
+        if !self.de.reader.next_element()? {
+            return Err(message("Expected a key and value pair"));
+        }
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        if !self.de.reader.next_element()? {
+            return Err(message("Expected a key and value pair"));
+        }
+        let value = seed.deserialize(&mut *self.de)?;
+        self.de.reader.close()?;
+        Ok(value)
+    }
+}
+
+struct Variant<'a, 'de: 'a> {
+    de: &'a mut Deserializer<'de>,
+    /// Whether the variant is a setting in a group, rather than a string
+    grouped: bool,
+    /// The length of the path before the variant, and where its value starts
+    current: Option<(usize, usize)>,
+}
+
+impl<'de> Variant<'_, 'de> {
+    /// Deserializes the value of the setting naming the variant with `f`,
+    /// then reads past the rest of its group.
+    fn value<T>(
+        self,
+        expected: &str,
+        f: impl FnOnce(&mut Deserializer<'de>) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let Some((len, start)) = self.current else {
+            return Err(de::Error::invalid_type(Unexpected::UnitVariant, &expected));
+        };
+        let res = f(self.de).and_then(|value| {
+            self.de.reader.end_setting()?;
+            self.de.reader.close()?;
+            Ok(value)
+        });
+        self.de.leave(len, Some(start), res)
+    }
+}
+
+impl<'de, 'a> EnumAccess<'de> for Variant<'a, 'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(mut self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        if !self.grouped {
+            let variant = match self.de.reader.scalar()? {
+                Scalar::Str(name) => {
+                    seed.deserialize(BorrowedStrDeserializer::<Error>::new(name))?
+                }
+                Scalar::Value(Value::String(name)) => {
+                    seed.deserialize(IntoDeserializer::<Error>::into_deserializer(name))?
+                }
+                found => return Err(invalid_type(describe(&found), "a string naming a variant")),
+            };
+            return Ok((variant, self));
+        }
+
+        let name = match self.de.reader.next_setting()? {
+            Some(Setting::Name(name)) => name,
+            _ => return Err(message("Expected a setting naming the variant")),
+        };
+        let variant = seed.deserialize(BorrowedStrDeserializer::<Error>::new(name))?;
+        let len = self.de.enter(Segment::Key(name));
+        self.current = Some((len, self.de.reader.offset()?));
+        Ok((variant, self))
+    }
+}
+
+impl<'de, 'a> VariantAccess<'de> for Variant<'a, 'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        if !self.grouped {
+            return Ok(());
+        }
+        self.value("unit variant", |de| Ok(de.reader.skip()?))
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.value("newtype variant", |de| match de.reader.peek()? {
+            kind @ (Kind::Array | Kind::List) => {
+                de.reader.open(kind);
+                if !de.reader.next_element()? {
+                    return Err(message("Expected 1 field in variant got 0"));
+                }
+                let value = seed.deserialize(&mut *de)?;
+                de.reader.close()?;
+                Ok(value)
+            }
+            _ => seed.deserialize(de),
+        })
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value("tuple variant", |de| {
+            de::Deserializer::deserialize_seq(de, visitor)
+        })
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value("struct variant", |de| {
+            de::Deserializer::deserialize_struct(de, "", fields, visitor)
+        })
+    }
+}
@@ -0,0 +1,457 @@
+use super::{error::Error, hex::HEX_TOKEN, value::INT64_TOKEN};
+use crate::{ArrayType, IntFormat, Value, convert::sequence};
+use indexmap::IndexMap;
+use serde::{Serialize, ser};
+
+/// Converts `value` to a [`Value`] without going through text. The result
+/// is what [`to_string`](crate::to_string) followed by
+/// [`Value::from_str`](std::str::FromStr::from_str) would give, and it fails
+/// where `to_string` does, such as for a `u64` above `i64::MAX`.
+pub fn to_value<T>(value: &T) -> Result<Value, Error>
+where
+    T: Serialize + ?Sized,
+{
+    value.serialize(ValueSerializer {
+        int_format: IntFormat::Decimal,
+        int64: false,
+    })
+}
+
+/// Builds a [`Value`] from the serde data model, with the same encoding as
+/// the text serializer.
+struct ValueSerializer {
+    /// Radix for integers, set while serializing the inside of a `Hex`
+    int_format: IntFormat,
+    /// Set while serializing an `int64` from a `Value`
+    int64: bool,
+}
+
+impl ValueSerializer {
+    fn int(&self, v: i64) -> Value {
+        match i32::try_from(v) {
+            Ok(v) if !self.int64 => Value::Int(v, self.int_format),
+            _ => Value::Int64(v, self.int_format),
+        }
+    }
+
+    fn element<T>(&self, value: &T) -> Result<Value, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(ValueSerializer {
+            int_format: self.int_format,
+            int64: false,
+        })
+    }
+}
+
+/// Wraps `value` in a group holding a single setting named `variant`, the
+/// encoding of enum variants with data.
+fn variant(variant: &'static str, value: Value) -> Value {
+    Value::Object(IndexMap::from([(variant.to_string(), value)]))
+}
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    type SerializeSeq = SerializeElements;
+    type SerializeTuple = SerializeElements;
+    type SerializeTupleStruct = SerializeElements;
+    type SerializeTupleVariant = SerializeElements;
+    type SerializeMap = SerializeSettings;
+    type SerializeStruct = SerializeSettings;
+    type SerializeStructVariant = SerializeSettings;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, Error> {
+        Ok(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, Error> {
+        Ok(self.int(v.into()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, Error> {
+        Ok(self.int(v.into()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, Error> {
+        Ok(self.int(v.into()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, Error> {
+        Ok(self.int(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, Error> {
+        Ok(self.int(v.into()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, Error> {
+        Ok(self.int(v.into()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, Error> {
+        Ok(self.int(v.into()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, Error> {
+        // libconfig has no unsigned 64-bit integers, as in `to_string`.
+        match i64::try_from(v) {
+            Ok(v) => Ok(self.int(v)),
+            Err(_) => Err(ser::Error::custom(format!("{v} does not fit in an int64"))),
+        }
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, Error> {
+        // Go through the shortest decimal form, as text does, so that 0.1f32
+        // becomes 0.1 rather than 0.10000000149011612.
+        Ok(Value::Float(v.to_string().parse().unwrap_or(v.into())))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, Error> {
+        Ok(Value::Float(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, Error> {
+        Ok(Value::String(v.into()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, Error> {
+        Ok(Value::String(v.into()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, Error> {
+        Ok(sequence(v.iter().map(|&b| self.int(b.into())).collect()))
+    }
+
+    fn serialize_none(self) -> Result<Value, Error> {
+        Ok(Value::Array(Vec::new(), ArrayType::Array))
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Value, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(sequence(vec![self.element(value)?]))
+    }
+
+    fn serialize_unit(self) -> Result<Value, Error> {
+        self.serialize_none()
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, Error> {
+        self.serialize_none()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value, Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Value, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        if name == HEX_TOKEN {
+            return value.serialize(ValueSerializer {
+                int_format: IntFormat::Hex,
+                ..self
+            });
+        }
+        if name == INT64_TOKEN {
+            return value.serialize(ValueSerializer {
+                int64: true,
+                ..self
+            });
+        }
+        Ok(Value::Array(vec![self.element(value)?], ArrayType::List))
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Value, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let value = Value::Array(vec![self.element(value)?], ArrayType::List);
+        Ok(variant(name, value))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeElements, Error> {
+        Ok(SerializeElements::new(self, len, ArrayType::List, None))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeElements, Error> {
+        Ok(SerializeElements::new(
+            self,
+            Some(len),
+            ArrayType::Array,
+            None,
+        ))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeElements, Error> {
+        self.serialize_tuple(len)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeElements, Error> {
+        Ok(SerializeElements::new(
+            self,
+            Some(len),
+            ArrayType::Array,
+            Some(variant),
+        ))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeSettings, Error> {
+        Ok(SerializeSettings::new(self, None))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<SerializeSettings, Error> {
+        Ok(SerializeSettings::new(self, None))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeSettings, Error> {
+        Ok(SerializeSettings::new(self, Some(variant)))
+    }
+}
+
+/// Collects the elements of an array or list. A sequence meant to be an
+/// array becomes a list unless its elements are scalars of one type.
+struct SerializeElements {
+    serializer: ValueSerializer,
+    elements: Vec<Value>,
+    array_type: ArrayType,
+    variant: Option<&'static str>,
+}
+
+impl SerializeElements {
+    fn new(
+        serializer: ValueSerializer,
+        len: Option<usize>,
+        array_type: ArrayType,
+        variant: Option<&'static str>,
+    ) -> Self {
+        SerializeElements {
+            serializer,
+            elements: Vec::with_capacity(len.unwrap_or(0)),
+            array_type,
+            variant,
+        }
+    }
+
+    fn push<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.elements.push(self.serializer.element(value)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value, Error> {
+        let value = match self.array_type {
+            ArrayType::Array => sequence(self.elements),
+            ArrayType::List => Value::Array(self.elements, ArrayType::List),
+        };
+        Ok(match self.variant {
+            Some(name) => variant(name, value),
+            None => value,
+        })
+    }
+}
+
+impl ser::SerializeSeq for SerializeElements {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SerializeElements {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeElements {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeElements {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+/// Collects the settings of a group.
+struct SerializeSettings {
+    serializer: ValueSerializer,
+    settings: IndexMap<String, Value>,
+    next_key: Option<String>,
+    variant: Option<&'static str>,
+}
+
+impl SerializeSettings {
+    fn new(serializer: ValueSerializer, variant: Option<&'static str>) -> Self {
+        SerializeSettings {
+            serializer,
+            settings: IndexMap::new(),
+            next_key: None,
+            variant,
+        }
+    }
+
+    fn insert<T>(&mut self, key: &str, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let value = self.serializer.element(value)?;
+        self.settings.insert(key.into(), value);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value, Error> {
+        let value = Value::Object(self.settings);
+        Ok(match self.variant {
+            Some(name) => variant(name, value),
+            None => value,
+        })
+    }
+}
+
+impl ser::SerializeMap for SerializeSettings {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        match self.serializer.element(key)? {
+            Value::String(key) => {
+                self.next_key = Some(key);
+                Ok(())
+            }
+            other => Err(ser::Error::custom(format!(
+                "setting names must be strings, found {}",
+                other.type_name()
+            ))),
+        }
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = self.next_key.take().ok_or_else(|| {
+            <Error as ser::Error>::custom("serialize_value called before serialize_key")
+        })?;
+        self.insert(&key, value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for SerializeSettings {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeSettings {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
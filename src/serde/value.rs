@@ -0,0 +1,163 @@
+use super::hex::Hex;
+use crate::{ArrayType, IntFormat, Value};
+use indexmap::IndexMap;
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, MapAccess, SeqAccess, Visitor},
+    ser::{SerializeMap, SerializeSeq, SerializeTuple},
+};
+use std::fmt;
+
+/// Name of the newtype struct [`Value`] deserializes as. The libconfig
+/// deserializer recognises it and marks each array and list with one of the
+/// keys below, so that the `ArrayType` survives.
+pub(crate) const VALUE_TOKEN: &str = "$__libconfig_rs_private_Value";
+pub(crate) const ARRAY_TOKEN: &str = "$__libconfig_rs_private_Array";
+pub(crate) const LIST_TOKEN: &str = "$__libconfig_rs_private_List";
+/// Name of the newtype struct an `int64` serializes as, and the key it is
+/// marked with when deserializing, so that a small `int64` keeps its width.
+pub(crate) const INT64_TOKEN: &str = "$__libconfig_rs_private_Int64";
+
+/// Arrays serialize as tuples, which the libconfig serializer writes as
+/// arrays, and lists as sequences. Other formats see both as sequences.
+/// An `int64` is wrapped in a newtype struct the libconfig serializers keep
+/// as an `int64`, even when it fits in 32 bits.
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Bool(b) => serializer.serialize_bool(*b),
+            Value::Int(i, IntFormat::Hex) => Hex(*i).serialize(serializer),
+            Value::Int(i, _) => serializer.serialize_i32(*i),
+            Value::Int64(i, IntFormat::Hex) => {
+                serializer.serialize_newtype_struct(INT64_TOKEN, &Hex(*i))
+            }
+            Value::Int64(i, _) => serializer.serialize_newtype_struct(INT64_TOKEN, i),
+            Value::UInt64(i, IntFormat::Hex) => Hex(*i).serialize(serializer),
+            Value::UInt64(i, _) => serializer.serialize_u64(*i),
+            Value::Float(f) => serializer.serialize_f64(*f),
+            Value::String(s) => serializer.serialize_str(s),
+            Value::Array(a, ArrayType::Array) => {
+                let mut tuple = serializer.serialize_tuple(a.len())?;
+                for v in a {
+                    tuple.serialize_element(v)?;
+                }
+                tuple.end()
+            }
+            Value::Array(a, ArrayType::List) => {
+                let mut seq = serializer.serialize_seq(Some(a.len()))?;
+                for v in a {
+                    seq.serialize_element(v)?;
+                }
+                seq.end()
+            }
+            Value::Object(o) => {
+                let mut map = serializer.serialize_map(Some(o.len()))?;
+                for (k, v) in o {
+                    map.serialize_entry(k, v)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+/// Sequences from formats other than libconfig become arrays when their
+/// elements are scalars of one type and lists otherwise. `null` becomes an
+/// empty array, which is how `None` is written.
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(VALUE_TOKEN, ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a libconfig value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    /// Integers are `int`s where they fit, as when read from libconfig text.
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Value, E> {
+        Ok(match i32::try_from(v) {
+            Ok(v) => Value::Int(v, IntFormat::Decimal),
+            Err(_) => Value::Int64(v, IntFormat::Decimal),
+        })
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Value, E> {
+        match i64::try_from(v) {
+            Ok(v) => self.visit_i64(v),
+            Err(_) => Ok(Value::UInt64(v, IntFormat::Decimal)),
+        }
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::Float(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::String(v.into()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Array(Vec::new(), ArrayType::Array))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+        self.visit_none()
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut elements: Vec<Value> = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(element) = seq.next_element()? {
+            elements.push(element);
+        }
+        Ok(Value::from(elements))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut settings = IndexMap::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some(key) = map.next_key::<String>()? {
+            let array_type = match key.as_str() {
+                ARRAY_TOKEN => ArrayType::Array,
+                LIST_TOKEN => ArrayType::List,
+                INT64_TOKEN => return Ok(Value::Int64(map.next_value()?, IntFormat::Decimal)),
+                _ => {
+                    let value = map.next_value()?;
+                    if settings.insert(key, value).is_some() {
+                        return Err(de::Error::custom("duplicate setting in group"));
+                    }
+                    continue;
+                }
+            };
+            return Ok(Value::Array(map.next_value()?, array_type));
+        }
+        Ok(Value::Object(settings))
+    }
+}
@@ -1,10 +1,15 @@
-use crate::{parser, printer};
+use crate::{
+    Conversion, FormatOptions, FromValue, LookupError, LookupErrorKind, ParseError, ParseOptions,
+    Violation, ViolationKind, parser, path, printer,
+};
 use indexmap::IndexMap;
-use nom::{
-    Finish,
-    error::{ErrorKind, ParseError},
+use nom::Finish;
+use std::{
+    fmt::{self, Write},
+    ops::{Index, IndexMut},
+    path::Path,
+    str::FromStr,
 };
-use std::{fmt, str::FromStr};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ArrayType {
@@ -12,48 +17,348 @@ pub enum ArrayType {
     List,
 }
 
+/// The radix an integer is written in, like libconfig's setting formats.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IntFormat {
+    #[default]
+    Decimal,
+    Hex,
+    Binary,
+    Octal,
+}
+
+impl IntFormat {
+    pub fn radix(self) -> u32 {
+        match self {
+            IntFormat::Decimal => 10,
+            IntFormat::Hex => 16,
+            IntFormat::Binary => 2,
+            IntFormat::Octal => 8,
+        }
+    }
+}
+
+/// A libconfig value.
+///
+/// Integers keep libconfig's distinction between `int` and `int64`: a literal
+/// with an `L` suffix is an `Int64`, as is one too large for 32 bits, which
+/// libconfig promotes rather than truncates. `UInt64` has no libconfig
+/// counterpart; it only holds literals above `i64::MAX` read with
+/// [`IntegerOverflow::U64`](crate::IntegerOverflow::U64).
 #[derive(Clone, Debug, PartialEq)]
 pub enum Value {
     Bool(bool),
-    Int(i64),
+    Int(i32, IntFormat),
+    Int64(i64, IntFormat),
+    UInt64(u64, IntFormat),
     Float(f64),
     String(String),
     Array(Vec<Value>, ArrayType),
     Object(IndexMap<String, Value>),
 }
 
+/// Writes a group as the top-level settings of a document, like
+/// [`Value::to_document_string`], and any other value as it is written after
+/// the name of a setting.
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "config : ")?;
+        let options = FormatOptions::default();
         let mut res = String::new();
-        printer::print(&mut res, self, 4);
-        write!(f, "{res};")?;
-        Ok(())
+        match self {
+            Value::Object(settings) => printer::print_document(&mut res, settings, &options),
+            value => printer::print(&mut res, value, 0, 0, &options),
+        }
+        write!(f, "{res}")
     }
 }
 
 impl FromStr for Value {
-    type Err = String;
+    type Err = ParseError;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        parser::root::<nom::error::Error<&str>>(input)
-            .finish()
-            .map(|(_, o)| o)
-            .map_err(|e| format!("{e}"))
+        Self::from_str_with_options(input, &ParseOptions::default())
     }
 }
 
 impl Value {
-    pub fn obj_from_str(input: &str) -> Result<IndexMap<String, Value>, nom::error::Error<&str>> {
-        parser::root::<nom::error::Error<&str>>(input)
+    /// Like [`Value::from_str`], with control over how the input is read.
+    pub fn from_str_with_options(input: &str, options: &ParseOptions) -> Result<Value, ParseError> {
+        parser::root::<parser::Error>(&parser::Context::new(options), input)
+            .finish()
+            .map(|(_, o)| o)
+            .map_err(|e| e.into_parse_error(input))
+    }
+
+    pub fn obj_from_str(input: &str) -> Result<IndexMap<String, Value>, ParseError> {
+        match Value::from_str(input)? {
+            Value::Object(map) => Ok(map),
+            _ => Err(ParseError::new(
+                input,
+                0,
+                Vec::new(),
+                Vec::new(),
+                Some("config did not have a group in the root".into()),
+            )),
+        }
+    }
+
+    /// Parses a whole libconfig document and returns its top-level settings,
+    /// in file order, as a `Value::Object`.
+    pub fn from_document_str(input: &str) -> Result<Value, ParseError> {
+        Self::from_document_str_with_options(input, &ParseOptions::default())
+    }
+
+    /// Like [`Value::from_document_str`], with control over how the input is read.
+    pub fn from_document_str_with_options(
+        input: &str,
+        options: &ParseOptions,
+    ) -> Result<Value, ParseError> {
+        Self::parse_document(parser::Context::new(options), input)
+    }
+
+    /// Reads a libconfig file, returning its top-level settings as a
+    /// `Value::Object`. Relative `@include` paths are resolved against the
+    /// current directory, as libconfig does when no include directory is set.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Value, ParseError> {
+        Self::from_file_with_options(path, &ParseOptions::default())
+    }
+
+    /// Like [`Value::from_file`], with control over how the file is read. The
+    /// file itself is read through the configured include resolver.
+    pub fn from_file_with_options(
+        path: impl AsRef<Path>,
+        options: &ParseOptions,
+    ) -> Result<Value, ParseError> {
+        let path = path.as_ref();
+        let input = options
+            .resolver()
+            .read(path)
+            .map_err(ParseError::unlocated)?;
+        Self::parse_document(parser::Context::for_file(options, path.into()), &input)
+            .map_err(|e| e.with_file(path))
+    }
+
+    fn parse_document(ctx: parser::Context, input: &str) -> Result<Value, ParseError> {
+        parser::document::<parser::Error>(&ctx, input)
             .finish()
-            .and_then(|(_, o)| match o {
-                Value::Object(map) => Ok(map),
-                _ => Err(nom::error::Error::from_error_kind(
-                    "Config did not have a object in the root",
-                    ErrorKind::Fail,
-                )),
-            })
+            .map(|(_, o)| Value::Object(o))
+            .map_err(|e| e.into_parse_error(input))
+    }
+
+    /// Writes the settings of a `Value::Object` as the top-level settings of a
+    /// libconfig document, the inverse of [`Value::from_document_str`].
+    /// Returns `None` for any other kind of value.
+    pub fn to_document_string(&self) -> Option<String> {
+        self.to_document_string_with_options(&FormatOptions::default())
+    }
+
+    /// Like [`Value::to_document_string`], with control over the layout.
+    pub fn to_document_string_with_options(&self, options: &FormatOptions) -> Option<String> {
+        let settings = self.as_obj()?;
+        let mut res = String::new();
+        printer::print_document(&mut res, settings, options);
+        Some(res)
+    }
+
+    /// Finds the setting at `path` below this value, like `config_lookup`.
+    ///
+    /// Path segments are setting names and `[n]` element indices, separated by
+    /// `.` (or `:` or `/`), e.g. `scene_manager.scenes.[0].root_entity.uuid`;
+    /// `scenes[0]` may also be written without the separator. An index into a
+    /// group counts its settings in order.
+    pub fn lookup(&self, path: &str) -> Result<&Value, LookupError> {
+        let mut value = self;
+        for (segment, start, end) in path::parse(path)? {
+            value = segment
+                .get(value)
+                .map_err(|kind| LookupError::new(path, start, end, kind))?;
+        }
+        Ok(value)
+    }
+
+    /// Looks up the setting at `path` and converts it to `T`, checking that
+    /// numbers fit. Integers and floats are not converted into each other; see
+    /// [`Value::lookup_value_with`].
+    pub fn lookup_value<T: FromValue>(&self, path: &str) -> Result<T, LookupError> {
+        self.lookup_value_with(path, Conversion::Strict)
+    }
+
+    /// Like [`Value::lookup_value`], with control over converting between
+    /// integers and floats.
+    pub fn lookup_value_with<T: FromValue>(
+        &self,
+        path: &str,
+        conversion: Conversion,
+    ) -> Result<T, LookupError> {
+        T::from_value(self.lookup(path)?, conversion).map_err(|kind| {
+            let (start, end) = path::last_span(path);
+            LookupError::new(path, start, end, kind)
+        })
+    }
+
+    /// Like [`Value::lookup_value`], but returns `default` when there is no
+    /// setting at `path`. A setting of the wrong type is still an error.
+    pub fn get_or<T: FromValue>(&self, path: &str, default: T) -> Result<T, LookupError> {
+        self.get_or_with(path, default, Conversion::Strict)
+    }
+
+    /// Like [`Value::get_or`], with control over converting between integers
+    /// and floats.
+    pub fn get_or_with<T: FromValue>(
+        &self,
+        path: &str,
+        default: T,
+        conversion: Conversion,
+    ) -> Result<T, LookupError> {
+        match self.lookup_value_with(path, conversion) {
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    LookupErrorKind::MissingKey | LookupErrorKind::IndexOutOfBounds { .. }
+                ) =>
+            {
+                Ok(default)
+            }
+            res => res,
+        }
+    }
+
+    /// Like [`Value::lookup`], returning a mutable reference.
+    pub fn lookup_mut(&mut self, path: &str) -> Result<&mut Value, LookupError> {
+        let mut value = self;
+        for (segment, start, end) in path::parse(path)? {
+            value = segment
+                .get_mut(value)
+                .map_err(|kind| LookupError::new(path, start, end, kind))?;
+        }
+        Ok(value)
+    }
+
+    /// Sets the setting at `path` to `value`, creating any missing groups
+    /// along the way, and returns the value it replaced. A `[n]` segment must
+    /// name an existing element.
+    pub fn set_path(&mut self, path: &str, value: Value) -> Result<Option<Value>, LookupError> {
+        let segments = path::parse(path)?;
+        let Some((&(last, start, end), parents)) = segments.split_last() else {
+            return Err(LookupError::new(path, 0, 0, LookupErrorKind::InvalidPath));
+        };
+
+        let mut parent = self;
+        for &(segment, start, end) in parents {
+            parent = match (segment, parent) {
+                (path::Segment::Key(key), Value::Object(o)) => o
+                    .entry(key.into())
+                    .or_insert_with(|| Value::Object(IndexMap::new())),
+                (segment, parent) => segment
+                    .get_mut(parent)
+                    .map_err(|kind| LookupError::new(path, start, end, kind))?,
+            };
+        }
+
+        let res = match (last, parent) {
+            (path::Segment::Key(key), Value::Object(o)) => Ok(o.insert(key.into(), value)),
+            (path::Segment::Index(index), Value::Array(a, array_type)) => {
+                check_element(array_type, &value)
+                    .and_then(|_| {
+                        let len = a.len();
+                        a.get_mut(index)
+                            .ok_or(LookupErrorKind::IndexOutOfBounds { index, len })
+                    })
+                    .map(|element| Some(std::mem::replace(element, value)))
+            }
+            (segment, parent) => segment
+                .get_mut(parent)
+                .map(|element| Some(std::mem::replace(element, value))),
+        };
+        res.map_err(|kind| LookupError::new(path, start, end, kind))
+    }
+
+    /// Removes the setting at `path` and returns it, or `None` if there is no
+    /// such setting. The order of the remaining settings is kept.
+    pub fn remove_path(&mut self, path: &str) -> Result<Option<Value>, LookupError> {
+        let segments = path::parse(path)?;
+        let Some((&(last, start, end), parents)) = segments.split_last() else {
+            return Err(LookupError::new(path, 0, 0, LookupErrorKind::InvalidPath));
+        };
+
+        let mut parent = self;
+        for &(segment, start, end) in parents {
+            parent = match segment.get_mut(parent) {
+                Ok(value) => value,
+                Err(LookupErrorKind::MissingKey | LookupErrorKind::IndexOutOfBounds { .. }) => {
+                    return Ok(None);
+                }
+                Err(kind) => return Err(LookupError::new(path, start, end, kind)),
+            };
+        }
+
+        let kind = match (last, parent) {
+            (path::Segment::Key(key), Value::Object(o)) => return Ok(o.shift_remove(key)),
+            (path::Segment::Index(index), Value::Object(o)) => {
+                return Ok(o.shift_remove_index(index).map(|(_, v)| v));
+            }
+            (path::Segment::Index(index), Value::Array(a, _)) => {
+                return Ok((index < a.len()).then(|| a.remove(index)));
+            }
+            (path::Segment::Key(_), _) => LookupErrorKind::NotAGroup,
+            (path::Segment::Index(_), _) => LookupErrorKind::NotAggregate,
+        };
+        Err(LookupError::new(path, start, end, kind))
+    }
+
+    /// Inserts `value` at position `index` of the array or list at `path`,
+    /// shifting later elements along. `index` may be the length, to append.
+    pub fn insert_at(&mut self, path: &str, index: usize, value: Value) -> Result<(), LookupError> {
+        let res = match self.lookup_mut(path)? {
+            Value::Array(a, _) if index > a.len() => Err(LookupErrorKind::IndexOutOfBounds {
+                index,
+                len: a.len(),
+            }),
+            Value::Array(a, array_type) => {
+                check_element(array_type, &value).map(|_| a.insert(index, value))
+            }
+            other => Err(LookupErrorKind::WrongType {
+                expected: "array or list",
+                actual: other.type_name(),
+            }),
+        };
+        res.map_err(|kind| {
+            let (start, end) = path::last_span(path);
+            LookupError::new(path, start, end, kind)
+        })
+    }
+
+    /// Checks the tree against libconfig's type rules: arrays hold scalars
+    /// of a single type, setting names are identifiers and integers fit in
+    /// 64 signed bits. Returns every violation found, in document order.
+    pub fn validate(&self) -> Result<(), Vec<Violation>> {
+        let mut violations = Vec::new();
+        validate(self, &mut String::new(), &mut violations);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// Whether this is a single value rather than a group, array or list.
+    pub fn is_scalar(&self) -> bool {
+        !matches!(self, Value::Array(..) | Value::Object(_))
+    }
+
+    /// The libconfig name of this kind of value, e.g. `int64` or `group`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Bool(_) => "bool",
+            Value::Int(..) => "int",
+            Value::Int64(..) => "int64",
+            Value::UInt64(..) => "unsigned int64",
+            Value::Float(_) => "float",
+            Value::String(_) => "string",
+            Value::Array(_, ArrayType::Array) => "array",
+            Value::Array(_, ArrayType::List) => "list",
+            Value::Object(_) => "group",
+        }
     }
 
     #[inline]
@@ -73,17 +378,49 @@ impl Value {
     }
 
     #[inline]
-    pub fn as_int(&self) -> Option<&i64> {
+    pub fn as_int(&self) -> Option<&i32> {
+        match self {
+            Value::Int(v, _) => Some(v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_int_mut(&mut self) -> Option<&mut i32> {
+        match self {
+            Value::Int(v, _) => Some(v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_int64(&self) -> Option<&i64> {
+        match self {
+            Value::Int64(v, _) => Some(v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_int64_mut(&mut self) -> Option<&mut i64> {
+        match self {
+            Value::Int64(v, _) => Some(v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_uint64(&self) -> Option<&u64> {
         match self {
-            Value::Int(v) => Some(v),
+            Value::UInt64(v, _) => Some(v),
             _ => None,
         }
     }
 
     #[inline]
-    pub fn as_int_mut(&mut self) -> Option<&mut i64> {
+    pub fn as_uint64_mut(&mut self) -> Option<&mut u64> {
         match self {
-            Value::Int(v) => Some(v),
+            Value::UInt64(v, _) => Some(v),
             _ => None,
         }
     }
@@ -152,3 +489,124 @@ impl Value {
         }
     }
 }
+
+/// Gets the setting named `key` in a group, like `value["scenes"]`.
+///
+/// # Panics
+///
+/// If this is not a group or it has no such setting; use [`Value::lookup`]
+/// to handle those cases.
+impl Index<&str> for Value {
+    type Output = Value;
+
+    fn index(&self, key: &str) -> &Value {
+        path::Segment::Key(key)
+            .get(self)
+            .unwrap_or_else(|kind| panic!("cannot index with \"{key}\": {kind}"))
+    }
+}
+
+impl IndexMut<&str> for Value {
+    fn index_mut(&mut self, key: &str) -> &mut Value {
+        path::Segment::Key(key)
+            .get_mut(self)
+            .unwrap_or_else(|kind| panic!("cannot index with \"{key}\": {kind}"))
+    }
+}
+
+/// Gets an element of an array or list, or a group's setting by position.
+///
+/// # Panics
+///
+/// If this is a scalar or `index` is out of bounds.
+impl Index<usize> for Value {
+    type Output = Value;
+
+    fn index(&self, index: usize) -> &Value {
+        path::Segment::Index(index)
+            .get(self)
+            .unwrap_or_else(|kind| panic!("cannot index with [{index}]: {kind}"))
+    }
+}
+
+impl IndexMut<usize> for Value {
+    fn index_mut(&mut self, index: usize) -> &mut Value {
+        path::Segment::Index(index)
+            .get_mut(self)
+            .unwrap_or_else(|kind| panic!("cannot index with [{index}]: {kind}"))
+    }
+}
+
+/// libconfig arrays hold only scalars; lists may hold anything.
+fn check_element(array_type: &ArrayType, value: &Value) -> Result<(), LookupErrorKind> {
+    match array_type {
+        ArrayType::Array if !value.is_scalar() => Err(LookupErrorKind::WrongType {
+            expected: "scalar",
+            actual: value.type_name(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+fn validate(value: &Value, path: &mut String, violations: &mut Vec<Violation>) {
+    let len = path.len();
+    match value {
+        Value::UInt64(i, _) if i64::try_from(*i).is_err() => {
+            violations.push(Violation::new(
+                path.clone(),
+                ViolationKind::OutOfRange {
+                    value: i.to_string(),
+                },
+            ));
+        }
+        Value::Array(a, array_type) => {
+            let first = a.first().map(Value::type_name);
+            for (i, element) in a.iter().enumerate() {
+                write!(path, "[{i}]").unwrap();
+                if *array_type == ArrayType::Array {
+                    let kind = if !element.is_scalar() {
+                        Some(ViolationKind::NonScalarInArray {
+                            actual: element.type_name(),
+                        })
+                    } else {
+                        first
+                            .filter(|&expected| expected != element.type_name())
+                            .map(|expected| ViolationKind::MixedArray {
+                                expected,
+                                actual: element.type_name(),
+                            })
+                    };
+                    if let Some(kind) = kind {
+                        violations.push(Violation::new(path.clone(), kind));
+                    }
+                }
+                validate(element, path, violations);
+                path.truncate(len);
+            }
+        }
+        Value::Object(o) => {
+            for (name, setting) in o {
+                if len > 0 {
+                    path.push('.');
+                }
+                path.push_str(name);
+                if !is_name(name) {
+                    violations.push(Violation::new(path.clone(), ViolationKind::InvalidName));
+                }
+                validate(setting, path, violations);
+                path.truncate(len);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Whether `name` can be written as a setting name, matching the parser's
+/// `[A-Za-z*][-A-Za-z0-9_*]*`.
+fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '*')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '*'))
+}
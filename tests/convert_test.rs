@@ -0,0 +1,209 @@
+use indexmap::IndexMap;
+use libconfig_rs::{ArrayType, IntFormat, LookupErrorKind, Value};
+use std::{collections::HashMap, str::FromStr};
+
+#[test]
+fn test_index() {
+    let mut config = Value::from_str(include_str!("../tests/1.vproj")).unwrap();
+
+    assert_eq!(
+        config["scene_manager"]["scenes"][0]["root_entity"]["uuid"].as_str(),
+        Some("a6e7b62e-e3e1-4f3c-92bf-8d871be72de3")
+    );
+    assert_eq!(
+        config["scene_manager"][0],
+        Value::Int(1, IntFormat::Decimal)
+    );
+
+    config["scene_manager"]["next_scene_id"] = Value::from(7);
+    config["scene_manager"]["scenes"][0]["scene_id"] = 3.into();
+    assert_eq!(
+        config.lookup_value::<i32>("scene_manager.next_scene_id"),
+        Ok(7)
+    );
+    assert_eq!(
+        config.lookup_value::<i32>("scene_manager.scenes.[0].scene_id"),
+        Ok(3)
+    );
+}
+
+#[test]
+#[should_panic(expected = "cannot index with \"missing\": no such setting")]
+fn test_index_missing() {
+    let config = Value::from_document_str("a = 1;").unwrap();
+    let _ = &config["missing"];
+}
+
+#[test]
+#[should_panic(expected = "cannot index with [2]: index 2 out of bounds, length is 2")]
+fn test_index_out_of_bounds() {
+    let config = Value::from(vec![1, 2]);
+    let _ = &config[2];
+}
+
+#[test]
+fn test_from_scalars() {
+    assert_eq!(Value::from(true), Value::Bool(true));
+    assert_eq!(Value::from(-5i8), Value::Int(-5, IntFormat::Decimal));
+    assert_eq!(Value::from(42u16), Value::Int(42, IntFormat::Decimal));
+    assert_eq!(Value::from(42i64), Value::Int64(42, IntFormat::Decimal));
+    assert_eq!(Value::from(42u32), Value::Int64(42, IntFormat::Decimal));
+    assert_eq!(
+        Value::from(u64::MAX),
+        Value::UInt64(u64::MAX, IntFormat::Decimal)
+    );
+    assert_eq!(Value::from(0.5f32), Value::Float(0.5));
+    assert_eq!(Value::from('x'), Value::String("x".into()));
+    assert_eq!(Value::from("abc"), Value::String("abc".into()));
+    assert_eq!(
+        Value::from(String::from("abc")),
+        Value::String("abc".into())
+    );
+}
+
+#[test]
+fn test_from_collections() {
+    assert_eq!(
+        Value::from(vec![1.0, 2.0]),
+        Value::Array(vec![Value::Float(1.0), Value::Float(2.0)], ArrayType::Array)
+    );
+    assert_eq!(
+        Value::from(vec![Value::from(1), Value::from("x")]),
+        Value::Array(
+            vec![Value::Int(1, IntFormat::Decimal), Value::String("x".into())],
+            ArrayType::List
+        )
+    );
+    assert_eq!(
+        Value::from(vec![vec![1], vec![2]]),
+        Value::Array(
+            vec![Value::from(vec![1]), Value::from(vec![2])],
+            ArrayType::List
+        )
+    );
+    assert_eq!(
+        Value::from(&[1, 2][..]),
+        Value::Array(
+            vec![
+                Value::Int(1, IntFormat::Decimal),
+                Value::Int(2, IntFormat::Decimal)
+            ],
+            ArrayType::Array
+        )
+    );
+    assert_eq!(
+        Value::from((1, 2)),
+        Value::Array(
+            vec![
+                Value::Int(1, IntFormat::Decimal),
+                Value::Int(2, IntFormat::Decimal)
+            ],
+            ArrayType::Array
+        )
+    );
+    assert_eq!(
+        Value::from((1, "x", true)),
+        Value::Array(
+            vec![
+                Value::Int(1, IntFormat::Decimal),
+                Value::String("x".into()),
+                Value::Bool(true)
+            ],
+            ArrayType::List
+        )
+    );
+
+    let mut map = IndexMap::new();
+    map.insert("a", 1);
+    map.insert("b", 2);
+    let value = Value::from(map);
+    assert_eq!(value.to_document_string().unwrap(), "a : 1;\nb : 2;\n");
+
+    let mut map = HashMap::new();
+    map.insert(String::from("a"), vec!["x"]);
+    assert_eq!(
+        Value::from(map).to_document_string().unwrap(),
+        "a : [ \"x\" ];\n"
+    );
+}
+
+#[test]
+fn test_try_from() {
+    let value = Value::from(42);
+    assert_eq!(i64::try_from(&value), Ok(42));
+    assert_eq!(u8::try_from(value.clone()), Ok(42));
+    assert_eq!(
+        i8::try_from(Value::from(300)),
+        Err(LookupErrorKind::OutOfRange {
+            expected: "i8",
+            value: "300".into()
+        })
+    );
+    assert_eq!(
+        String::try_from(value),
+        Err(LookupErrorKind::WrongType {
+            expected: "string",
+            actual: "int"
+        })
+    );
+    assert_eq!(String::try_from(Value::from("x")), Ok("x".into()));
+    assert_eq!(f64::try_from(&Value::from(0.5)), Ok(0.5));
+    assert_eq!(bool::try_from(Value::from(true)), Ok(true));
+
+    assert_eq!(
+        Vec::<i32>::try_from(Value::from(vec![1, 2])),
+        Ok(vec![1, 2])
+    );
+    assert_eq!(
+        <(i32, String)>::try_from(Value::from((1, "x"))),
+        Ok((1, "x".into()))
+    );
+    assert!(<(i32, i32)>::try_from(Value::from((1, 2, 3))).is_err());
+
+    let config = Value::from_document_str("a = 1; b = 2;").unwrap();
+    let map = HashMap::<String, u32>::try_from(config.clone()).unwrap();
+    assert_eq!(map["b"], 2);
+    let map = IndexMap::<String, Value>::try_from(config).unwrap();
+    assert_eq!(map.keys().collect::<Vec<_>>(), ["a", "b"]);
+}
+
+#[test]
+fn test_wide_integers_and_chars() {
+    assert_eq!(
+        Value::try_from(-5i128),
+        Ok(Value::Int64(-5, IntFormat::Decimal))
+    );
+    assert_eq!(
+        Value::try_from(u128::from(u64::MAX)),
+        Ok(Value::UInt64(u64::MAX, IntFormat::Decimal))
+    );
+    assert_eq!(
+        Value::try_from(i128::from(i64::MIN) - 1),
+        Err(LookupErrorKind::OutOfRange {
+            expected: "int64",
+            value: "-9223372036854775809".into()
+        })
+    );
+    assert!(Value::try_from(u128::MAX).is_err());
+
+    assert_eq!(i128::try_from(Value::from(i64::MIN)), Ok(i64::MIN.into()));
+    assert_eq!(u128::try_from(&Value::from(u64::MAX)), Ok(u64::MAX.into()));
+    assert!(u128::try_from(Value::from(-1)).is_err());
+
+    assert_eq!(char::try_from(Value::from('é')), Ok('é'));
+    assert_eq!(
+        char::try_from(Value::from("ab")),
+        Err(LookupErrorKind::OutOfRange {
+            expected: "char",
+            value: "\"ab\"".into()
+        })
+    );
+    assert!(char::try_from(&Value::from("")).is_err());
+    assert_eq!(
+        char::try_from(Value::from(1)),
+        Err(LookupErrorKind::WrongType {
+            expected: "char",
+            actual: "int"
+        })
+    );
+}
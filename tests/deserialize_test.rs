@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{collections::HashMap, str::FromStr};
 
 #[derive(Deserialize)]
 struct Test1 {
@@ -204,3 +204,161 @@ fn test8() {
         assert_eq!(t.a[&2], "two");
     }
 }
+
+#[derive(Deserialize, PartialEq, Debug)]
+struct TestAutoconvert {
+    a: i32,
+    b: f64,
+    c: u8,
+}
+
+#[test]
+fn test_autoconvert() {
+    let config = "config : { a = 2.7; b = 3; c = 4.0; };";
+    assert!(libconfig_rs::from_str::<TestAutoconvert>(config).is_err());
+
+    let options = libconfig_rs::ParseOptions::new().autoconvert(true);
+    let t = libconfig_rs::from_str_with_options::<TestAutoconvert>(config, &options).unwrap();
+    assert_eq!(t, TestAutoconvert { a: 2, b: 3.0, c: 4 });
+
+    let options = libconfig_rs::ParseOptions::new().strict_terminators(true);
+    assert!(
+        libconfig_rs::from_str_with_options::<TestAutoconvert>("config : { a = 1 };", &options)
+            .is_err()
+    );
+}
+
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)]
+struct SceneManager {
+    scene_manager: Scenes,
+}
+
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)]
+struct Scenes {
+    scenes: Vec<Scene>,
+}
+
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)]
+struct Scene {
+    scene_id: u32,
+    kind: Kind,
+}
+
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)]
+enum Kind {
+    Menu,
+    Level(u32),
+}
+
+#[test]
+fn test_error_location() {
+    let config = r#"config : {
+    scene_manager : {
+        scenes = (
+            { scene_id = 1; kind = "Menu"; },
+            { scene_id = "two"; kind = { Level: (2); }; }
+        );
+    };
+};"#;
+    let err = libconfig_rs::from_str::<SceneManager>(config).unwrap_err();
+
+    assert_eq!(err.path(), "scene_manager.scenes[1].scene_id");
+    assert_eq!(err.location(), Some((5, 26)));
+    assert!(matches!(
+        err.kind(),
+        libconfig_rs::ErrorKind::InvalidType { found, expected }
+            if found == "string \"two\"" && expected == "an integer"
+    ));
+    assert_eq!(
+        err.to_string(),
+        "line 5, column 26: \"scene_manager.scenes[1].scene_id\": \
+         invalid type: string \"two\", expected an integer"
+    );
+}
+
+#[test]
+fn test_error_kinds() {
+    let config = "config : { scene_manager : { scenes = ( { kind = \"Menu\"; } ); }; };";
+    let err = libconfig_rs::from_str::<SceneManager>(config).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        libconfig_rs::ErrorKind::MissingField("scene_id")
+    ));
+    assert_eq!(err.path(), "scene_manager.scenes[0]");
+    assert_eq!(err.location(), Some((1, 41)));
+
+    let config =
+        "config : { scene_manager : { scenes = ( { scene_id = 1; kind = \"Boss\"; } ); }; };";
+    let err = libconfig_rs::from_str::<SceneManager>(config).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        libconfig_rs::ErrorKind::UnknownVariant { variant, expected }
+            if variant == "Boss" && *expected == ["Menu", "Level"]
+    ));
+    assert_eq!(err.path(), "scene_manager.scenes[0].kind");
+
+    let config = "config : { scene_manager : { scenes = ( { scene_id = 1; kind = { Level: (\"x\"); }; } ); }; };";
+    let err = libconfig_rs::from_str::<SceneManager>(config).unwrap_err();
+    assert_eq!(err.path(), "scene_manager.scenes[0].kind.Level");
+
+    let err = libconfig_rs::from_str::<SceneManager>("config : { scene_manager = 1 ").unwrap_err();
+    assert!(matches!(err.kind(), libconfig_rs::ErrorKind::Syntax(_)));
+    assert_eq!(err.location(), Some((1, 30)));
+
+    let value = libconfig_rs::Value::from_str("config : { scene_manager : 1; };").unwrap();
+    let err = libconfig_rs::from_value::<SceneManager>(value).unwrap_err();
+    assert_eq!(err.path(), "scene_manager");
+    assert_eq!(err.location(), None);
+}
+
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)]
+struct TestRange {
+    small: u8,
+    big: u64,
+}
+
+#[test]
+fn test_integer_range() {
+    assert!(libconfig_rs::from_str::<u8>("config : 255;").is_ok());
+    let err = libconfig_rs::from_str::<u8>("config : 300;").unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        libconfig_rs::ErrorKind::InvalidValue { found, expected }
+            if found == "int `300`" && expected == "a u8"
+    ));
+    assert_eq!(err.location(), Some((1, 10)));
+
+    let err = libconfig_rs::from_str::<u64>("config : -1;").unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        libconfig_rs::ErrorKind::InvalidValue { .. }
+    ));
+    assert!(libconfig_rs::from_str::<i8>("config : -129;").is_err());
+    assert!(libconfig_rs::from_str::<i32>("config : 3000000000L;").is_err());
+
+    let config = "config : {\n  small = 1;\n  big = -5;\n};";
+    let err = libconfig_rs::from_str::<TestRange>(config).unwrap_err();
+    assert_eq!(err.path(), "big");
+    assert_eq!(err.location(), Some((3, 9)));
+    assert_eq!(
+        err.to_string(),
+        "line 3, column 9: \"big\": invalid value: int `-5`, expected a u64"
+    );
+
+    // Values built without text are checked the same way.
+    let value = libconfig_rs::Value::from_str("config : { small = 256; big = 1; };").unwrap();
+    let err = libconfig_rs::from_value::<TestRange>(value).unwrap_err();
+    assert_eq!(err.path(), "small");
+    assert!(matches!(
+        err.kind(),
+        libconfig_rs::ErrorKind::InvalidValue { .. }
+    ));
+
+    let options = libconfig_rs::ParseOptions::new().autoconvert(true);
+    assert!(libconfig_rs::from_str_with_options::<u8>("config : 1e10;", &options).is_err());
+}
@@ -0,0 +1,156 @@
+use libconfig_rs::{DuplicateSettings, ParseOptions, Value, libconfig};
+use std::str::FromStr;
+
+#[test]
+fn test_unclosed_group() {
+    let config = "config : {\n    a = 1;\n    b = 2;\n    c = 3 ]\n};";
+    let err = Value::from_str(config).unwrap_err();
+
+    assert_eq!(err.offset(), 43);
+    assert_eq!((err.line(), err.column()), (4, 11));
+    assert_eq!(err.snippet(), "    c = 3 ]");
+    assert_eq!(err.expected(), ["'}'"]);
+    assert_eq!(err.context(), ["map"]);
+    assert_eq!(err.reason(), None);
+}
+
+#[test]
+fn test_missing_value() {
+    let config = "config : {\n\ta = (1, { b = ; });\n};";
+    let err = Value::from_str(config).unwrap_err();
+
+    assert_eq!((err.line(), err.column()), (2, 16));
+    assert_eq!(err.expected(), ["value"]);
+    assert_eq!(err.context(), ["map", "list", "map"]);
+}
+
+#[test]
+fn test_expected_close() {
+    let err = Value::from_str("config : { a = [1, 2; };").unwrap_err();
+
+    assert_eq!(err.column(), 21);
+    assert_eq!(err.expected(), ["']'"]);
+    assert_eq!(err.context(), ["map", "array"]);
+}
+
+#[test]
+fn test_bad_key() {
+    let err = Value::from_str("5 = 1;").unwrap_err();
+
+    assert_eq!((err.line(), err.column()), (1, 1));
+    assert_eq!(err.expected(), ["ident"]);
+}
+
+#[test]
+fn test_unterminated_comment() {
+    let err = Value::from_str("config : { a = 1; /* never closed };").unwrap_err();
+
+    assert_eq!(err.column(), 19);
+    assert_eq!(err.reason(), Some("unterminated block comment"));
+}
+
+#[test]
+fn test_unterminated_string() {
+    let err = Value::from_str("config : \"abc").unwrap_err();
+
+    assert_eq!(err.column(), 14);
+    assert_eq!(err.expected(), ["'\"'"]);
+    assert_eq!(
+        err.to_string(),
+        "line 1, column 14: unexpected end of input, expected '\"'\n  \
+           |\n\
+         1 | config : \"abc\n  \
+           |              ^\n  \
+           = while parsing value > string"
+    );
+}
+
+#[test]
+fn test_display() {
+    let config = "config : {\n    a = 1;\n    b = 2;\n    c = 3 ]\n};";
+    let err = Value::from_str(config).unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "line 4, column 11: expected '}'\n  \
+           |\n\
+         4 |     c = 3 ]\n  \
+           |           ^\n  \
+           = while parsing map"
+    );
+}
+
+#[test]
+fn test_document_trailing_input() {
+    let err = Value::from_document_str("a = 1;\n}").unwrap_err();
+
+    assert_eq!((err.line(), err.column()), (2, 1));
+    assert_eq!(err.expected(), ["end of input"]);
+}
+
+#[test]
+fn test_obj_from_str_requires_group() {
+    let err = Value::obj_from_str("config : 1;").unwrap_err();
+    assert_eq!(
+        err.reason(),
+        Some("config did not have a group in the root")
+    );
+}
+
+#[test]
+fn test_expected_one_of() {
+    let err = Value::from_str("config { };").unwrap_err();
+
+    assert_eq!(err.column(), 8);
+    assert_eq!(err.expected(), ["':'", "'='"]);
+    assert!(
+        err.to_string()
+            .starts_with("line 1, column 8: expected one of ':', '='\n")
+    );
+}
+
+#[test]
+fn test_integer_overflow() {
+    let config = "id = 1;\ncount = 99999999999999999999;";
+    let err = Value::from_document_str(config).unwrap_err();
+
+    assert_eq!((err.line(), err.column()), (2, 9));
+    assert_eq!(err.reason(), Some("integer does not fit in 64 bits"));
+
+    assert!(Value::from_document_str("a = -9223372036854775809;").is_err());
+    assert!(Value::from_document_str("a = 0x10000000000000000;").is_err());
+}
+
+#[test]
+fn test_duplicate_setting() {
+    let config = "a = 1;\ngroup = {\n  b = 2;\n  b = 3;\n};";
+    let err = Value::from_document_str(config).unwrap_err();
+
+    assert_eq!((err.line(), err.column()), (4, 3));
+    assert_eq!(err.reason(), Some("duplicate setting \"b\""));
+    assert_eq!(err.first_location(), Some((3, 3)));
+    assert!(
+        err.to_string().starts_with(
+            "line 4, column 3: duplicate setting \"b\", first set at line 3, column 3\n"
+        )
+    );
+}
+
+#[test]
+fn test_duplicate_policy() {
+    let config = "a = 1; b = 2; a = 3;";
+    let parse = |policy| {
+        let options = ParseOptions::new().duplicate_settings(policy);
+        Value::from_document_str_with_options(config, &options)
+    };
+
+    assert!(parse(DuplicateSettings::Error).is_err());
+    assert_eq!(
+        parse(DuplicateSettings::LastWins).unwrap(),
+        libconfig!({ a: 3; b: 2; })
+    );
+    assert_eq!(
+        parse(DuplicateSettings::FirstWins).unwrap(),
+        libconfig!({ a: 1; b: 2; })
+    );
+}
@@ -0,0 +1,225 @@
+use indexmap::IndexMap;
+use libconfig_rs::{DuplicateSettings, IncludeResolver, IntFormat, ParseOptions, Value};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// Serves includes from memory, keyed by path relative to the include directory.
+struct MemoryResolver(HashMap<PathBuf, String>);
+
+impl MemoryResolver {
+    fn new(files: &[(&str, &str)]) -> Self {
+        MemoryResolver(
+            files
+                .iter()
+                .map(|(path, content)| (PathBuf::from(path), content.to_string()))
+                .collect(),
+        )
+    }
+}
+
+impl IncludeResolver for MemoryResolver {
+    fn resolve(&self, include_dir: Option<&Path>, path: &str) -> Result<Vec<PathBuf>, String> {
+        Ok(vec![match include_dir {
+            Some(dir) => dir.join(path),
+            None => PathBuf::from(path),
+        }])
+    }
+
+    fn read(&self, path: &Path) -> Result<String, String> {
+        self.0
+            .get(path)
+            .cloned()
+            .ok_or_else(|| format!("{} not found", path.display()))
+    }
+}
+
+fn obj(settings: &[(&str, Value)]) -> Value {
+    Value::Object(
+        settings
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect::<IndexMap<_, _>>(),
+    )
+}
+
+#[test]
+fn test_include_top_level() {
+    let options = ParseOptions::new()
+        .include_resolver(MemoryResolver::new(&[("common.cfg", "b = 2; c = 3;")]));
+    let config = r#"
+        a = 1;
+        @include "common.cfg"
+        d = 4;
+    "#;
+    let res = Value::from_document_str_with_options(config, &options).unwrap();
+
+    assert_eq!(
+        res,
+        obj(&[
+            ("a", Value::Int(1, IntFormat::Decimal)),
+            ("b", Value::Int(2, IntFormat::Decimal)),
+            ("c", Value::Int(3, IntFormat::Decimal)),
+            ("d", Value::Int(4, IntFormat::Decimal)),
+        ])
+    );
+}
+
+#[test]
+fn test_include_in_group() {
+    let options =
+        ParseOptions::new().include_resolver(MemoryResolver::new(&[("inner.cfg", "y = true;")]));
+    let config = r#"config : { x = 1; @include "inner.cfg" };"#;
+    let res = Value::from_document_str_with_options(config, &options).unwrap();
+
+    assert_eq!(
+        res,
+        obj(&[(
+            "config",
+            obj(&[
+                ("x", Value::Int(1, IntFormat::Decimal)),
+                ("y", Value::Bool(true))
+            ])
+        )])
+    );
+}
+
+#[test]
+fn test_include_nested_with_dir() {
+    let options = ParseOptions::new()
+        .include_dir("conf")
+        .include_resolver(MemoryResolver::new(&[
+            ("conf/a.cfg", r#"a = 1; @include "b.cfg""#),
+            ("conf/b.cfg", "b = 2;"),
+        ]));
+    let res = Value::from_document_str_with_options(r#"@include "a.cfg""#, &options).unwrap();
+
+    assert_eq!(
+        res,
+        obj(&[
+            ("a", Value::Int(1, IntFormat::Decimal)),
+            ("b", Value::Int(2, IntFormat::Decimal))
+        ])
+    );
+}
+
+#[test]
+fn test_include_cycle() {
+    let options = ParseOptions::new().include_resolver(MemoryResolver::new(&[
+        ("a.cfg", r#"@include "b.cfg""#),
+        ("b.cfg", r#"@include "a.cfg""#),
+    ]));
+    let err = Value::from_document_str_with_options(r#"@include "a.cfg""#, &options).unwrap_err();
+
+    assert_eq!(err.reason(), Some("include cycle: a.cfg -> b.cfg -> a.cfg"));
+    assert_eq!(err.file(), Some(Path::new("b.cfg")));
+    assert_eq!((err.line(), err.column()), (1, 1));
+}
+
+#[test]
+fn test_include_self_from_file() {
+    let options = ParseOptions::new().include_resolver(MemoryResolver::new(&[(
+        "main.cfg",
+        r#"a = 1; @include "main.cfg""#,
+    )]));
+    let err = Value::from_file_with_options("main.cfg", &options).unwrap_err();
+
+    assert_eq!(err.reason(), Some("include cycle: main.cfg -> main.cfg"));
+    assert_eq!(err.file(), Some(Path::new("main.cfg")));
+    assert_eq!(err.column(), 8);
+}
+
+#[test]
+fn test_include_missing() {
+    let options = ParseOptions::new().include_resolver(MemoryResolver::new(&[]));
+    let err =
+        Value::from_document_str_with_options(r#"@include "gone.cfg""#, &options).unwrap_err();
+
+    assert_eq!(err.reason(), Some("gone.cfg not found"));
+    assert_eq!(err.file(), None);
+}
+
+#[test]
+fn test_include_glob_from_disk() {
+    let options = ParseOptions::new().include_dir("tests/include");
+    let res = Value::from_file_with_options("tests/include/main.cfg", &options).unwrap();
+
+    assert_eq!(
+        res,
+        obj(&[
+            ("name", Value::String("main".into())),
+            (
+                "window",
+                obj(&[
+                    ("width", Value::Int(800, IntFormat::Decimal)),
+                    ("height", Value::Int(600, IntFormat::Decimal))
+                ])
+            ),
+            ("audio", obj(&[("volume", Value::Float(0.5))])),
+        ])
+    );
+}
+
+#[test]
+fn test_include_syntax_error_location() {
+    let options = ParseOptions::new()
+        .include_resolver(MemoryResolver::new(&[("broken.cfg", "a = 1;\nb = ;\n")]));
+    let config = "x = 1;\n@include \"broken.cfg\"\n";
+    let err = Value::from_document_str_with_options(config, &options).unwrap_err();
+
+    assert_eq!(err.file(), Some(Path::new("broken.cfg")));
+    assert_eq!((err.line(), err.column()), (2, 5));
+    assert_eq!(err.expected(), ["value"]);
+}
+
+#[test]
+fn test_include_duplicate() {
+    let resolver = || MemoryResolver::new(&[("extra.cfg", "a = 2;\nb = 3;\n")]);
+    let config = "a = 1;\n@include \"extra.cfg\"\n";
+
+    let options = ParseOptions::new().include_resolver(resolver());
+    let err = Value::from_document_str_with_options(config, &options).unwrap_err();
+    assert_eq!((err.line(), err.column()), (2, 1));
+    assert_eq!(err.reason(), Some("duplicate setting \"a\""));
+    assert_eq!(err.first_location(), Some((1, 1)));
+
+    let options = ParseOptions::new()
+        .include_resolver(resolver())
+        .duplicate_settings(DuplicateSettings::LastWins);
+    let res = Value::from_document_str_with_options(config, &options).unwrap();
+    assert_eq!(
+        res,
+        obj(&[
+            ("a", Value::Int(2, IntFormat::Decimal)),
+            ("b", Value::Int(3, IntFormat::Decimal)),
+        ])
+    );
+}
+
+#[test]
+fn test_include_duplicate_location() {
+    let config = "b = 1;\n  @include \"extra.cfg\"\na = 1;\n";
+    let options = ParseOptions::new()
+        .include_resolver(MemoryResolver::new(&[("extra.cfg", "a = 2;\nb = 3;\n")]));
+    let err = Value::from_document_str_with_options(config, &options).unwrap_err();
+    assert_eq!((err.line(), err.column()), (2, 3));
+    assert_eq!(err.reason(), Some("duplicate setting \"b\""));
+    assert_eq!(err.first_location(), Some((1, 1)));
+
+    // A setting first read from an included file was set at the `@include`.
+    let config = "@include \"extra.cfg\"\nc = 0;\n  a = 1;\n";
+    let err = Value::from_document_str_with_options(config, &options).unwrap_err();
+    assert_eq!((err.line(), err.column()), (3, 3));
+    assert_eq!(err.reason(), Some("duplicate setting \"a\""));
+    assert_eq!(err.first_location(), Some((1, 1)));
+
+    let config = "config : {\n  @include \"extra.cfg\"\n  a = 1;\n};";
+    let err =
+        libconfig_rs::from_str_with_options::<HashMap<String, i32>>(config, &options).unwrap_err();
+    let libconfig_rs::ErrorKind::Syntax(err) = err.kind() else {
+        panic!("expected a syntax error, got {err}");
+    };
+    assert_eq!((err.line(), err.column()), (3, 3));
+    assert_eq!(err.first_location(), Some((2, 3)));
+}
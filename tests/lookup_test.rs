@@ -0,0 +1,405 @@
+use libconfig_rs::{Conversion, IntFormat, LookupErrorKind, Value};
+use std::{path::PathBuf, str::FromStr};
+
+fn vproj() -> Value {
+    Value::from_str(include_str!("../tests/1.vproj")).unwrap()
+}
+
+#[test]
+fn test_lookup() {
+    let config = vproj();
+
+    let uuid = config
+        .lookup("scene_manager.scenes.[0].root_entity.uuid")
+        .unwrap();
+    assert_eq!(uuid.as_str(), Some("a6e7b62e-e3e1-4f3c-92bf-8d871be72de3"));
+
+    let id = config.lookup("scene_manager.next_scene_id").unwrap();
+    assert_eq!(id, &Value::Int(1, IntFormat::Decimal));
+}
+
+#[test]
+fn test_lookup_path_syntax() {
+    let config = vproj();
+    let expected = config
+        .lookup("scene_manager.scenes.[0].root_entity.children.[0].position.[2]")
+        .unwrap();
+    assert_eq!(expected, &Value::Float(-2.0));
+
+    for path in [
+        "scene_manager.scenes[0].root_entity.children[0].position[2]",
+        "scene_manager/scenes/[0]/root_entity/children/[0]/position/[2]",
+        "scene_manager:scenes:[0]:root_entity:children:[0]:position:[2]",
+    ] {
+        assert_eq!(config.lookup(path).unwrap(), expected, "{path}");
+    }
+
+    assert_eq!(config.lookup("").unwrap(), &config);
+    // An index into a group counts its settings.
+    assert_eq!(
+        config.lookup("scene_manager.[0]").unwrap(),
+        &Value::Int(1, IntFormat::Decimal)
+    );
+}
+
+#[test]
+fn test_lookup_errors() {
+    let config = vproj();
+
+    let err = config.lookup("scene_manager.scene.[0]").unwrap_err();
+    assert_eq!(err.kind(), &LookupErrorKind::MissingKey);
+    assert_eq!(err.segment(), "scene");
+    assert_eq!(err.failed_path(), "scene_manager.scene");
+    assert_eq!(
+        err.to_string(),
+        "lookup of \"scene_manager.scene.[0]\" failed at \"scene\": no such setting"
+    );
+
+    let err = config.lookup("scene_manager.scenes.[3].uuid").unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &LookupErrorKind::IndexOutOfBounds { index: 3, len: 1 }
+    );
+    assert_eq!(err.segment(), "[3]");
+
+    let err = config.lookup("scene_manager.next_scene_id.x").unwrap_err();
+    assert_eq!(err.kind(), &LookupErrorKind::NotAGroup);
+    assert_eq!(err.segment(), "x");
+
+    let err = config.lookup("scene_manager.scenes.uuid").unwrap_err();
+    assert_eq!(err.kind(), &LookupErrorKind::NotAGroup);
+
+    let err = config.lookup("scene_manager.next_scene_id[0]").unwrap_err();
+    assert_eq!(err.kind(), &LookupErrorKind::NotAggregate);
+    assert_eq!(err.segment(), "[0]");
+
+    for path in ["a..b", "a.", "a.[x]", "a[0", "a[0]b"] {
+        let err = config.lookup(path).unwrap_err();
+        assert_eq!(err.kind(), &LookupErrorKind::InvalidPath, "{path}");
+    }
+}
+
+#[test]
+fn test_lookup_mut() {
+    let mut config = vproj();
+
+    *config
+        .lookup_mut("scene_manager.scenes[0].root_entity.uuid")
+        .unwrap() = Value::String("changed".into());
+    *config
+        .lookup_mut("scene_manager.next_scene_id")
+        .unwrap()
+        .as_int_mut()
+        .unwrap() += 1;
+
+    assert_eq!(
+        config
+            .lookup("scene_manager.scenes.[0].root_entity.uuid")
+            .unwrap()
+            .as_str(),
+        Some("changed")
+    );
+    assert_eq!(
+        config
+            .lookup("scene_manager.next_scene_id")
+            .unwrap()
+            .as_int(),
+        Some(&2)
+    );
+    assert!(config.lookup_mut("scene_manager.missing").is_err());
+}
+
+fn settings() -> Value {
+    Value::from_document_str(
+        "port = 8080; big = 70000; neg = -1; ratio = 0.5; whole = 3.0; huge = 1e300; \
+         on = true; name = \"svc\"; dir = \"/var/lib/svc\"; id = 5000000000L; \
+         group = { list = ( 1, 2 ); };",
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_lookup_value() {
+    let config = settings();
+
+    assert_eq!(config.lookup_value::<i32>("port"), Ok(8080));
+    assert_eq!(config.lookup_value::<u16>("port"), Ok(8080));
+    assert_eq!(config.lookup_value::<i64>("id"), Ok(5000000000));
+    assert_eq!(config.lookup_value::<f64>("ratio"), Ok(0.5));
+    assert_eq!(config.lookup_value::<f32>("ratio"), Ok(0.5));
+    assert_eq!(config.lookup_value::<bool>("on"), Ok(true));
+    assert_eq!(
+        config.lookup_value::<String>("name"),
+        Ok(String::from("svc"))
+    );
+    assert_eq!(
+        config.lookup_value::<PathBuf>("dir"),
+        Ok(PathBuf::from("/var/lib/svc"))
+    );
+    assert_eq!(config.lookup_value::<u8>("group.list.[1]"), Ok(2));
+}
+
+#[test]
+fn test_lookup_value_errors() {
+    let config = settings();
+
+    let err = config.lookup_value::<u16>("big").unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &LookupErrorKind::OutOfRange {
+            expected: "u16",
+            value: "70000".into()
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "lookup of \"big\" failed at \"big\": 70000 is out of range for u16"
+    );
+    assert!(config.lookup_value::<u32>("neg").is_err());
+    assert!(config.lookup_value::<i32>("id").is_err());
+    assert!(config.lookup_value::<f32>("huge").is_err());
+
+    let err = config.lookup_value::<i32>("group.list").unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &LookupErrorKind::WrongType {
+            expected: "i32",
+            actual: "list"
+        }
+    );
+    assert_eq!(err.segment(), "list");
+
+    let err = config.lookup_value::<String>("group.list[0]").unwrap_err();
+    assert_eq!(err.segment(), "[0]");
+    assert_eq!(
+        err.to_string(),
+        "lookup of \"group.list[0]\" failed at \"[0]\": expected string, found int"
+    );
+
+    // Without auto-conversion, ints and floats are distinct.
+    assert!(config.lookup_value::<f64>("port").is_err());
+    assert!(config.lookup_value::<i32>("whole").is_err());
+}
+
+#[test]
+fn test_lookup_value_autoconvert() {
+    let config = settings();
+
+    assert_eq!(
+        config.lookup_value_with::<f64>("port", Conversion::Auto),
+        Ok(8080.0)
+    );
+    assert_eq!(
+        config.lookup_value_with::<f32>("id", Conversion::Auto),
+        Ok(5e9)
+    );
+    assert_eq!(
+        config.lookup_value_with::<i32>("whole", Conversion::Auto),
+        Ok(3)
+    );
+    assert_eq!(
+        config.lookup_value_with::<i32>("ratio", Conversion::Auto),
+        Ok(0)
+    );
+    assert!(
+        config
+            .lookup_value_with::<i64>("huge", Conversion::Auto)
+            .is_err()
+    );
+    assert!(
+        config
+            .lookup_value_with::<bool>("port", Conversion::Auto)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_lookup_value_autoconvert_limits() {
+    let config = Value::from_document_str(
+        "below = 9223372036854774784.0; two63 = 9223372036854775808.0;\n\
+         two64 = 18446744073709551616.0; min = -9223372036854775808.0;",
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.lookup_value_with::<i64>("below", Conversion::Auto),
+        Ok(9223372036854774784)
+    );
+    assert_eq!(
+        config.lookup_value_with::<i64>("min", Conversion::Auto),
+        Ok(i64::MIN)
+    );
+    let err = config
+        .lookup_value_with::<i64>("two63", Conversion::Auto)
+        .unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &LookupErrorKind::OutOfRange {
+            expected: "i64",
+            value: "9223372036854776000".into()
+        }
+    );
+    assert_eq!(
+        config.lookup_value_with::<u64>("two63", Conversion::Auto),
+        Ok(1 << 63)
+    );
+    assert!(
+        config
+            .lookup_value_with::<u64>("two64", Conversion::Auto)
+            .is_err()
+    );
+    assert!(
+        config
+            .lookup_value_with::<usize>("two64", Conversion::Auto)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_get_or() {
+    let config = settings();
+
+    assert_eq!(config.get_or("port", 80), Ok(8080));
+    assert_eq!(config.get_or("timeout", 30), Ok(30));
+    assert_eq!(config.get_or("group.missing.x", 1.5), Ok(1.5));
+    assert_eq!(config.get_or("group.list.[5]", 0), Ok(0));
+    assert!(config.get_or("name", 0).is_err());
+    assert_eq!(
+        config.get_or_with("port", 1.0, Conversion::Auto),
+        Ok(8080.0)
+    );
+}
+
+#[test]
+fn test_set_path() {
+    let mut config =
+        Value::from_document_str("a = { b = 1; }; arr = [1, 2]; l = (1, \"x\");").unwrap();
+
+    let previous = config.set_path("a.b", Value::Int(2, IntFormat::Decimal));
+    assert_eq!(previous, Ok(Some(Value::Int(1, IntFormat::Decimal))));
+
+    // Missing groups on the way are created.
+    assert_eq!(config.set_path("a.c.d.e", Value::Bool(true)), Ok(None));
+    assert_eq!(config.lookup_value::<bool>("a.c.d.e"), Ok(true));
+
+    assert_eq!(
+        config.set_path("arr.[1]", Value::Int(5, IntFormat::Hex)),
+        Ok(Some(Value::Int(2, IntFormat::Decimal)))
+    );
+    assert_eq!(
+        config.to_document_string().unwrap(),
+        "a : {\n    b : 2;\n    c : {\n        d : {\n            e : true;\n        };\n    };\n};\n\
+         arr : [ 1, 0x5 ];\nl : ( 1, \"x\" );\n"
+    );
+
+    // Arrays only hold scalars; lists hold anything.
+    let err = config
+        .set_path("arr.[0]", Value::Object(Default::default()))
+        .unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &LookupErrorKind::WrongType {
+            expected: "scalar",
+            actual: "group"
+        }
+    );
+    assert!(
+        config
+            .set_path("l.[1]", Value::Object(Default::default()))
+            .is_ok()
+    );
+
+    let err = config
+        .set_path("arr.[2]", Value::Int(1, IntFormat::Decimal))
+        .unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &LookupErrorKind::IndexOutOfBounds { index: 2, len: 2 }
+    );
+
+    let err = config.set_path("a.b.x", Value::Bool(true)).unwrap_err();
+    assert_eq!(err.kind(), &LookupErrorKind::NotAGroup);
+    assert_eq!(err.segment(), "x");
+
+    assert!(config.set_path("", Value::Bool(true)).is_err());
+}
+
+#[test]
+fn test_remove_path() {
+    let mut config =
+        Value::from_document_str("a = { b = 1; c = 2; d = 3; }; l = (1, 2, 3);").unwrap();
+
+    assert_eq!(
+        config.remove_path("a.c"),
+        Ok(Some(Value::Int(2, IntFormat::Decimal)))
+    );
+    assert_eq!(
+        config.remove_path("l.[0]"),
+        Ok(Some(Value::Int(1, IntFormat::Decimal)))
+    );
+    assert_eq!(config.remove_path("a.c"), Ok(None));
+    assert_eq!(config.remove_path("x.y.z"), Ok(None));
+    assert_eq!(config.remove_path("l.[5]"), Ok(None));
+    assert_eq!(
+        config.remove_path("a.b.c").unwrap_err().kind(),
+        &LookupErrorKind::NotAGroup
+    );
+
+    assert_eq!(
+        config.to_document_string().unwrap(),
+        "a : {\n    b : 1;\n    d : 3;\n};\nl : ( 2, 3 );\n"
+    );
+}
+
+#[test]
+fn test_insert_at() {
+    let mut config = Value::from_document_str("g = { arr = [1, 3]; }; l = ();").unwrap();
+
+    config
+        .insert_at("g.arr", 1, Value::Int(2, IntFormat::Decimal))
+        .unwrap();
+    config
+        .insert_at("g.arr", 3, Value::Int(4, IntFormat::Decimal))
+        .unwrap();
+    config
+        .insert_at("l", 0, Value::Object(Default::default()))
+        .unwrap();
+    assert_eq!(
+        config.to_document_string().unwrap(),
+        "g : {\n    arr : [ 1, 2, 3, 4 ];\n};\nl : ( {\n    } );\n"
+    );
+
+    let err = config
+        .insert_at("g.arr", 9, Value::Int(0, IntFormat::Decimal))
+        .unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &LookupErrorKind::IndexOutOfBounds { index: 9, len: 4 }
+    );
+    assert_eq!(err.segment(), "arr");
+
+    let err = config
+        .insert_at(
+            "g.arr",
+            0,
+            Value::Array(vec![], libconfig_rs::ArrayType::List),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &LookupErrorKind::WrongType {
+            expected: "scalar",
+            actual: "list"
+        }
+    );
+
+    let err = config.insert_at("g", 0, Value::Bool(true)).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &LookupErrorKind::WrongType {
+            expected: "array or list",
+            actual: "group"
+        }
+    );
+    assert!(config.insert_at("missing", 0, Value::Bool(true)).is_err());
+}
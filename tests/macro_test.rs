@@ -0,0 +1,94 @@
+use libconfig_rs::{ArrayType, IntFormat, Value, libconfig};
+
+#[test]
+fn test_scalars() {
+    assert_eq!(libconfig!(1), Value::Int(1, IntFormat::Decimal));
+    assert_eq!(libconfig!(-1), Value::Int(-1, IntFormat::Decimal));
+    assert_eq!(libconfig!(2.5), Value::Float(2.5));
+    assert_eq!(libconfig!("x"), Value::String("x".into()));
+    assert_eq!(libconfig!(true), Value::Bool(true));
+}
+
+#[test]
+fn test_nested() {
+    let value = libconfig!({
+        a: 1;
+        b: [1, 2];
+        c: ( "x", { d: true; } );
+        e = { f = 1.5, g = "h" },
+        empty_group: {};
+        empty_array: [];
+        empty_list: ();
+    });
+
+    let expected = Value::from_document_str(
+        r#"
+        a = 1;
+        b = [1, 2];
+        c = ( "x", { d = true; } );
+        e = { f = 1.5; g = "h"; };
+        empty_group = {};
+        empty_array = [];
+        empty_list = ();
+        "#,
+    )
+    .unwrap();
+    assert_eq!(value, expected);
+}
+
+#[test]
+fn test_array_types() {
+    assert_eq!(
+        libconfig!([1, 2]),
+        Value::Array(
+            vec![
+                Value::Int(1, IntFormat::Decimal),
+                Value::Int(2, IntFormat::Decimal)
+            ],
+            ArrayType::Array
+        )
+    );
+    assert_eq!(
+        libconfig!((1, 2,)),
+        Value::Array(
+            vec![
+                Value::Int(1, IntFormat::Decimal),
+                Value::Int(2, IntFormat::Decimal)
+            ],
+            ArrayType::List
+        )
+    );
+    assert_eq!(
+        libconfig!(([1], [2, 3])),
+        Value::Array(vec![libconfig!([1]), libconfig!([2, 3])], ArrayType::List)
+    );
+}
+
+#[test]
+fn test_interpolation() {
+    let width = 800;
+    let name = String::from("main");
+    let tags = vec!["a", "b"];
+    let inner = libconfig!({ x: 1; });
+
+    let value = libconfig!({
+        name: name.clone();
+        size: { width: width; height: width * 3 / 4; }
+        tags: tags;
+        ids: [width, width + 1];
+        inner: inner.clone();
+        "with-dash": 1i64 << 40;
+        last: Value::from(name.len())
+    });
+
+    assert_eq!(value["name"], Value::String("main".into()));
+    assert_eq!(value.lookup_value::<i32>("size.height"), Ok(600));
+    assert_eq!(value["tags"], Value::from(vec!["a", "b"]));
+    assert_eq!(value.lookup_value::<i32>("ids.[1]"), Ok(801));
+    assert_eq!(value["inner"], inner);
+    assert_eq!(
+        value["with-dash"],
+        Value::Int64(1 << 40, IntFormat::Decimal)
+    );
+    assert_eq!(value["last"], Value::Int64(4, IntFormat::Decimal));
+}
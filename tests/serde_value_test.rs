@@ -0,0 +1,97 @@
+use libconfig_rs::{ArrayType, IntFormat, Value, libconfig};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Plugin {
+    name: String,
+    settings: Value,
+}
+
+#[test]
+fn test_embedded_value() {
+    let config = r#"config : {
+        name = "blur";
+        settings = {
+            radius = 2.5;
+            passes = [1, 2, 3];
+            mixed = ( 1, "two", { three = 3; } );
+            mask = 0xFF;
+            big = 10000000000L;
+            small = 5L;
+        };
+    };"#;
+    let plugin: Plugin = libconfig_rs::from_str(config).unwrap();
+    assert_eq!(plugin.name, "blur");
+
+    let settings = &plugin.settings;
+    assert_eq!(settings["radius"], Value::Float(2.5));
+    assert_eq!(settings["passes"], libconfig!([1, 2, 3]));
+    assert_eq!(settings["mixed"], libconfig!((1, "two", { three: 3; })));
+    assert_eq!(
+        settings["big"],
+        Value::Int64(10000000000, IntFormat::Decimal)
+    );
+    assert_eq!(settings["small"], Value::Int64(5, IntFormat::Decimal));
+
+    let ser = libconfig_rs::to_string(&plugin).unwrap();
+    assert!(ser.contains("passes : [ 1, 2, 3 ];"));
+    assert!(ser.contains("small : 5L;"));
+    let der: Plugin = libconfig_rs::from_str(&ser).unwrap();
+    assert_eq!(der, plugin);
+
+    assert_eq!(
+        libconfig_rs::to_value(&settings["small"]).unwrap(),
+        Value::Int64(5, IntFormat::Decimal)
+    );
+    let der: Plugin = libconfig_rs::from_value(libconfig_rs::to_value(&plugin).unwrap()).unwrap();
+    assert_eq!(der, plugin);
+}
+
+#[test]
+fn test_array_types_survive() {
+    let value = libconfig!({
+        array: [1, 2];
+        list: (1, 2);
+        empty_array: [];
+        empty_list: ();
+    });
+    let ser = libconfig_rs::to_string(&value).unwrap();
+    assert_eq!(
+        ser,
+        "config : {\n    array : [ 1, 2 ];\n    list : ( 1, 2 );\n    empty_array : [ ];\n    empty_list : ( );\n};\n"
+    );
+    let der: Value = libconfig_rs::from_str(&ser).unwrap();
+    assert_eq!(der, value);
+
+    let hex = Value::Int(255, IntFormat::Hex);
+    assert_eq!(libconfig_rs::to_string(&hex).unwrap(), "config : 0xFF;\n");
+}
+
+#[test]
+fn test_json() {
+    let value = Value::from_document_str(
+        r#"
+        name = "x";
+        sizes = [1, 2];
+        items = ( { id = 1; }, "two" );
+        "#,
+    )
+    .unwrap();
+
+    let json = serde_json::to_string(&value).unwrap();
+    assert_eq!(
+        json,
+        r#"{"name":"x","sizes":[1,2],"items":[{"id":1},"two"]}"#
+    );
+
+    let back: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, value);
+
+    let back: Value = serde_json::from_str(r#"{"a":[1.5,2.5],"b":[1,"x"],"c":null}"#).unwrap();
+    assert_eq!(back["a"], libconfig!([1.5, 2.5]));
+    assert_eq!(
+        back["b"],
+        Value::Array(vec![libconfig!(1), libconfig!("x")], ArrayType::List)
+    );
+    assert_eq!(back["c"], libconfig!([]));
+}
@@ -22,6 +22,85 @@ fn test_integer() {
     assert_eq!(test, der);
 }
 
+#[test]
+fn test_int64_suffix() {
+    let test = TestInteger {
+        a: 1,
+        b: 2,
+        c: 3,
+        d: 4,
+    };
+    let ser = libconfig_rs::to_string(&test).unwrap();
+    assert_eq!(
+        ser,
+        "config : {\n    a : 1;\n    b : 2;\n    c : 3;\n    d : 4;\n};\n"
+    );
+
+    let test = TestUnsignedInteger {
+        a: 1,
+        b: 2,
+        c: u32::MAX,
+        d: 1 << 40,
+    };
+    let ser = libconfig_rs::to_string(&test).unwrap();
+    assert_eq!(
+        ser,
+        "config : {\n    a : 1;\n    b : 2;\n    c : 4294967295L;\n    d : 1099511627776L;\n};\n"
+    );
+    let der = libconfig_rs::from_str(&ser).unwrap();
+    assert_eq!(test, der);
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct TestLongString {
+    inner: TestLongStringInner,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct TestLongStringInner {
+    a: String,
+}
+
+#[test]
+fn test_split_long_strings() {
+    let test = TestLongString {
+        inner: TestLongStringInner {
+            a: "a long line\nof text".into(),
+        },
+    };
+    let options = libconfig_rs::FormatOptions::new().split_strings(8);
+    let ser = libconfig_rs::to_string_with_options(&test, &options).unwrap();
+    assert_eq!(
+        ser,
+        "config : {\n    inner : {\n        a : \"a long \"\n            \"line\\nof \"\n            \"text\";\n    };\n};\n"
+    );
+    let der = libconfig_rs::from_str(&ser).unwrap();
+    assert_eq!(test, der);
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct TestHex {
+    mask: libconfig_rs::Hex<u32>,
+    flags: Vec<libconfig_rs::Hex<i32>>,
+    plain: i32,
+}
+
+#[test]
+fn test_hex() {
+    let test = TestHex {
+        mask: libconfig_rs::Hex(255),
+        flags: vec![libconfig_rs::Hex(16), libconfig_rs::Hex(-1)],
+        plain: 10,
+    };
+    let ser = libconfig_rs::to_string(&test).unwrap();
+    assert_eq!(
+        ser,
+        "config : {\n    mask : 0xFF;\n    flags : ( 0x10, 0xFFFFFFFF );\n    plain : 10;\n};\n"
+    );
+    let der = libconfig_rs::from_str(&ser).unwrap();
+    assert_eq!(test, der);
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
 struct TestUnsignedInteger {
     a: u8,
@@ -114,6 +193,55 @@ fn test_string_special_chars() {
     assert_eq!(test, der);
 }
 
+#[test]
+fn test_libconfig_escapes() {
+    let test = TestString {
+        a: "bell\u{7}".into(),
+        b: "esc\u{1b}".into(),
+    };
+    let options = libconfig_rs::FormatOptions::new().escapes(libconfig_rs::EscapeStyle::Libconfig);
+    let ser = libconfig_rs::to_string_with_options(&test, &options).unwrap();
+    assert_eq!(
+        ser,
+        "config : {\n    a : \"bell\\x07\";\n    b : \"esc\\x1B\";\n};\n"
+    );
+    let der: TestString = libconfig_rs::from_str(&ser).unwrap();
+    assert_eq!(test, der);
+}
+
+#[test]
+fn test_default_escapes() {
+    // By default only escapes the C libconfig reads are written; combining
+    // characters stay as UTF-8.
+    let test = TestString {
+        a: "cafe\u{301} \u{1b}".into(),
+        b: "tab\tquote\"".into(),
+    };
+    let ser = libconfig_rs::to_string(&test).unwrap();
+    assert_eq!(
+        ser,
+        "config : {\n    a : \"cafe\u{301} \\x1B\";\n    b : \"tab\\tquote\\\"\";\n};\n"
+    );
+    assert!(!ser.contains("\\u"));
+    let der: TestString = libconfig_rs::from_str(&ser).unwrap();
+    assert_eq!(test, der);
+}
+
+#[test]
+fn test_terminator() {
+    let test = TestLongString {
+        inner: TestLongStringInner { a: "x".into() },
+    };
+    let options = libconfig_rs::FormatOptions::new().terminator(libconfig_rs::Terminator::Omit);
+    let ser = libconfig_rs::to_string_with_options(&test, &options).unwrap();
+    assert_eq!(
+        ser,
+        "config : {\n    inner : {\n        a : \"x\"\n    }\n}\n"
+    );
+    let der: TestLongString = libconfig_rs::from_str(&ser).unwrap();
+    assert_eq!(test, der);
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
 struct TestArray {
     a: [i32; 2],
@@ -287,3 +415,276 @@ fn test_enum() {
     let der = libconfig_rs::from_str(&ser).unwrap();
     assert_eq!(test, der);
 }
+
+#[test]
+fn test_format_options() {
+    let test = TestStruct {
+        a: UnitStruct,
+        b: Struct { a: 1, b: 2.5 },
+        c: StructInStruct {
+            a: Struct { a: 3, b: 4.0 },
+        },
+    };
+    let options = libconfig_rs::FormatOptions::new()
+        .indent(libconfig_rs::Indent::Spaces(2))
+        .assignment(libconfig_rs::Assignment::Equals)
+        .colon_for_groups(true)
+        .brace_on_own_line(true)
+        .float_precision(3);
+    let ser = libconfig_rs::to_string_with_options(&test, &options).unwrap();
+    assert_eq!(
+        ser,
+        "config :\n{\n  a = [ ];\n  b :\n  {\n    a = 1;\n    b = 2.500;\n  };\n  c :\n  {\n    a :\n    {\n      a = 3;\n      b = 4.000;\n    };\n  };\n};\n"
+    );
+    let der: TestStruct = libconfig_rs::from_str(&ser).unwrap();
+    assert_eq!(test, der);
+
+    // The serializer and the `Value` writer lay out the same data identically.
+    let value = libconfig_rs::Value::from_document_str(&ser).unwrap();
+    assert_eq!(
+        value.to_document_string_with_options(&options).unwrap(),
+        ser
+    );
+
+    let ser = libconfig_rs::to_string_with_options(&1, &options.trailing_newline(false)).unwrap();
+    assert_eq!(ser, "config = 1;");
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct TestWide {
+    short: Vec<i32>,
+    numbers: Vec<u32>,
+    points: Vec<(i32, i32)>,
+    structs: Vec<Struct>,
+}
+
+#[test]
+fn test_max_width() {
+    let test = TestWide {
+        short: vec![1, 2, 3],
+        numbers: vec![100000, 200000, 300000, 400000, 500000],
+        points: vec![(1, 2), (3, 4), (5, 6), (7, 8), (9, 10)],
+        structs: vec![Struct { a: 1, b: 2.5 }],
+    };
+    let options = libconfig_rs::FormatOptions::new().max_width(40);
+    let ser = libconfig_rs::to_string_with_options(&test, &options).unwrap();
+    assert_eq!(
+        ser,
+        r#"config : {
+    short : ( 1, 2, 3 );
+    numbers : (
+        100000,
+        200000,
+        300000,
+        400000,
+        500000
+    );
+    points : (
+        [ 1, 2 ],
+        [ 3, 4 ],
+        [ 5, 6 ],
+        [ 7, 8 ],
+        [ 9, 10 ]
+    );
+    structs : (
+        {
+            a : 1;
+            b : 2.5;
+        }
+    );
+};
+"#
+    );
+    let der: TestWide = libconfig_rs::from_str(&ser).unwrap();
+    assert_eq!(test, der);
+
+    // The same data written through `Value` is laid out the same way.
+    let value = libconfig_rs::Value::from_document_str(&ser).unwrap();
+    assert_eq!(
+        value.to_document_string_with_options(&options).unwrap(),
+        ser
+    );
+}
+
+#[derive(Serialize)]
+struct TestFit {
+    v: Vec<i32>,
+    inner: TestFitInner,
+}
+
+#[derive(Serialize)]
+struct TestFitInner {
+    pairs: Vec<(i32, i32)>,
+    name: String,
+}
+
+#[test]
+fn test_max_width_matches_value() {
+    let test = TestFit {
+        v: (0..10).collect(),
+        inner: TestFitInner {
+            pairs: vec![(1, 2), (3, 4), (5, 6)],
+            name: "inner".into(),
+        },
+    };
+    let value =
+        libconfig_rs::Value::from_document_str(&libconfig_rs::to_string(&test).unwrap()).unwrap();
+
+    // `v : ( 0, 1, 2, 3, 4, 5, 6, 7, 8, 9 );` is 41 columns wide.
+    let options = libconfig_rs::FormatOptions::new().max_width(40);
+    let ser = libconfig_rs::to_string_with_options(&test, &options).unwrap();
+    assert!(ser.lines().all(|line| line.len() <= 40), "{ser}");
+    let options = libconfig_rs::FormatOptions::new().max_width(41);
+    let ser = libconfig_rs::to_string_with_options(&test, &options).unwrap();
+    assert!(
+        ser.contains("    v : ( 0, 1, 2, 3, 4, 5, 6, 7, 8, 9 );\n"),
+        "{ser}"
+    );
+
+    for width in 20..=120 {
+        for options in [
+            libconfig_rs::FormatOptions::new(),
+            libconfig_rs::FormatOptions::new()
+                .assignment(libconfig_rs::Assignment::Equals)
+                .colon_for_groups(true)
+                .brace_on_own_line(true),
+        ] {
+            let options = options.max_width(width);
+            assert_eq!(
+                libconfig_rs::to_string_with_options(&test, &options).unwrap(),
+                value.to_document_string_with_options(&options).unwrap(),
+                "max_width {width}"
+            );
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Rgb(u8, u8, u8);
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Meters(f64);
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+enum Shape {
+    Rect(f32, f32),
+    Labeled(String, i32),
+    Point,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct TestShapes {
+    color: Rgb,
+    length: Meters,
+    rect: Shape,
+    labeled: Shape,
+    point: Shape,
+    mixed: (String, i32),
+    nested: Option<Struct>,
+}
+
+#[test]
+fn test_tuple_shapes() {
+    let test = TestShapes {
+        color: Rgb(255, 128, 0),
+        length: Meters(1.5),
+        rect: Shape::Rect(2.0, 3.5),
+        labeled: Shape::Labeled("a".into(), 1),
+        point: Shape::Point,
+        mixed: ("x".into(), 2),
+        nested: Some(Struct { a: 1, b: 2.0 }),
+    };
+    let ser = libconfig_rs::to_string(&test).unwrap();
+    assert_eq!(
+        ser,
+        r#"config : {
+    color : [ 255, 128, 0 ];
+    length : ( 1.5 );
+    rect : {
+        Rect : [ 2.0, 3.5 ];
+    };
+    labeled : {
+        Labeled : ( "a", 1 );
+    };
+    point : "Point";
+    mixed : ( "x", 2 );
+    nested : ( {
+            a : 1;
+            b : 2.0;
+        } );
+};
+"#
+    );
+    let der = libconfig_rs::from_str(&ser).unwrap();
+    assert_eq!(test, der);
+
+    let value = libconfig_rs::Value::from_document_str(&ser).unwrap();
+    assert_eq!(value.validate(), Ok(()));
+}
+
+/// Bytes, serialized through `serialize_bytes` like `serde_bytes` does.
+#[derive(PartialEq, Debug)]
+struct Bytes(Vec<u8>);
+
+impl Serialize for Bytes {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Bytes {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BytesVisitor;
+
+        impl serde::de::Visitor<'_> for BytesVisitor {
+            type Value = Bytes;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("bytes")
+            }
+
+            fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Bytes, E> {
+                Ok(Bytes(v))
+            }
+        }
+
+        deserializer.deserialize_byte_buf(BytesVisitor)
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct TestBytes {
+    a: Bytes,
+    b: Bytes,
+}
+
+#[test]
+fn test_bytes() {
+    let test = TestBytes {
+        a: Bytes(vec![0, 1, 255]),
+        b: Bytes(vec![]),
+    };
+    let ser = libconfig_rs::to_string(&test).unwrap();
+    assert_eq!(
+        ser,
+        "config : {\n    a : [ 0, 1, 255 ];\n    b : [ ];\n};\n"
+    );
+    let der: TestBytes = libconfig_rs::from_str(&ser).unwrap();
+    assert_eq!(test, der);
+
+    let der: TestBytes = libconfig_rs::from_str("config : { a = \"hi\"; b = (); };").unwrap();
+    assert_eq!(der.a, Bytes(b"hi".to_vec()));
+    assert!(libconfig_rs::from_str::<TestBytes>("config : { a = [256]; b = []; };").is_err());
+}
+
+#[test]
+fn test_u64_range() {
+    let max = i64::MAX as u64;
+    let ser = libconfig_rs::to_string(&max).unwrap();
+    assert_eq!(ser, "config : 9223372036854775807L;\n");
+    assert_eq!(libconfig_rs::from_str::<u64>(&ser).unwrap(), max);
+
+    // libconfig has no unsigned 64-bit integers to write larger values as.
+    assert!(libconfig_rs::to_string(&(max + 1)).is_err());
+    assert!(libconfig_rs::to_string(&u64::MAX).is_err());
+}
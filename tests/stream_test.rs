@@ -0,0 +1,114 @@
+use libconfig_rs::{Deserializer, IncludeResolver, ParseOptions, Value};
+use serde::Deserialize;
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Borrowed<'a> {
+    name: &'a str,
+    #[serde(borrow)]
+    title: Cow<'a, str>,
+    tags: Vec<&'a str>,
+}
+
+#[test]
+fn test_borrowed_strings() {
+    let config = r#"config : {
+        name = "player";
+        title = "first\tsecond";
+        tags = [ "a", "b" ];
+    };"#;
+    let res = libconfig_rs::from_str::<Borrowed>(config).unwrap();
+
+    assert_eq!(res.name, "player");
+    assert_eq!(res.tags, ["a", "b"]);
+    assert_eq!(res.title, "first\tsecond");
+    assert!(matches!(res.title, Cow::Owned(_)));
+
+    // Only strings that are exactly as written in the input can be borrowed.
+    let config = r#"config : { name = "a\"b"; title = ""; tags = []; };"#;
+    assert!(libconfig_rs::from_str::<Borrowed>(config).is_err());
+    let config = r#"config : { name = "a" "b"; title = ""; tags = []; };"#;
+    assert!(libconfig_rs::from_str::<Borrowed>(config).is_err());
+}
+
+#[test]
+fn test_deserializer() {
+    let config = "config : ( 1, 2 ); // done\n";
+    let mut de = Deserializer::new(config).unwrap();
+    let res = Vec::<i32>::deserialize(&mut de).unwrap();
+    de.end().unwrap();
+    assert_eq!(res, [1, 2]);
+
+    let mut de = Deserializer::new("config : ( 1, 2 ); extra").unwrap();
+    Vec::<i32>::deserialize(&mut de).unwrap();
+    assert!(de.end().is_err());
+}
+
+#[test]
+fn test_value_matches_parser() {
+    let config = r#"config : {
+        a = 1;
+        b = [ 1.5, 2.5 ];
+        c = ( "x", { d = true; }, ( ) );
+        f = "multi" "part";
+    };"#;
+    let streamed = libconfig_rs::from_str::<Value>(config).unwrap();
+    assert_eq!(streamed, Value::from_str(config).unwrap());
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Included {
+    a: i32,
+    b: i32,
+    c: String,
+}
+
+struct Resolver;
+
+impl IncludeResolver for Resolver {
+    fn resolve(&self, _include_dir: Option<&Path>, path: &str) -> Result<Vec<PathBuf>, String> {
+        Ok(vec![PathBuf::from(path)])
+    }
+
+    fn read(&self, _path: &Path) -> Result<String, String> {
+        Ok("b = 2; c = \"three\";".into())
+    }
+}
+
+#[test]
+fn test_include() {
+    let options = ParseOptions::new().include_resolver(Resolver);
+    let config = r#"config : { a = 1; @include "common.cfg" };"#;
+    let res = libconfig_rs::from_str_with_options::<Included>(config, &options).unwrap();
+    assert_eq!(
+        res,
+        Included {
+            a: 1,
+            b: 2,
+            c: "three".into()
+        }
+    );
+
+    let config = r#"config : { b = 1; @include "common.cfg" a = 1; };"#;
+    let err = libconfig_rs::from_str_with_options::<Included>(config, &options).unwrap_err();
+    assert!(matches!(err.kind(), libconfig_rs::ErrorKind::Syntax(_)));
+}
+
+#[test]
+fn test_errors() {
+    let err = libconfig_rs::from_str::<Included>("config : { a = 1; a = 2; };").unwrap_err();
+    assert!(matches!(err.kind(), libconfig_rs::ErrorKind::Syntax(_)));
+    assert_eq!(err.location(), Some((1, 19)));
+
+    let config = "config : { a = 1; b = 2; c = \"3\"; }; trailing";
+    let err = libconfig_rs::from_str::<Included>(config).unwrap_err();
+    assert!(matches!(err.kind(), libconfig_rs::ErrorKind::Syntax(_)));
+
+    // Settings the struct does not have are still checked for syntax.
+    let config = "config : { a = 1; b = 2; c = \"3\"; d = [ 1, ( ]; };";
+    assert!(libconfig_rs::from_str::<Included>(config).is_err());
+}
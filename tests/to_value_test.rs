@@ -0,0 +1,112 @@
+use libconfig_rs::{Hex, Value, from_value, from_value_ref, libconfig, to_value};
+use serde::{Deserialize, Serialize, de::IntoDeserializer};
+use std::{collections::BTreeMap, str::FromStr};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+struct Window {
+    title: String,
+    width: u32,
+    scale: f32,
+    mask: Hex<u32>,
+    big: i64,
+    tags: Vec<String>,
+    pos: (i32, i32),
+    mixed: (String, i32),
+    parent: Option<Box<Window>>,
+    mode: Mode,
+    extra: BTreeMap<String, bool>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+enum Mode {
+    Windowed,
+    Fullscreen(u8),
+    Split(f64, f64),
+    Custom { rate: u32 },
+}
+
+fn window() -> Window {
+    Window {
+        title: "main".into(),
+        width: 800,
+        scale: 0.1,
+        mask: Hex(0xFF),
+        big: 1 << 40,
+        tags: vec!["a".into(), "b".into()],
+        pos: (1, 2),
+        mixed: ("x".into(), 3),
+        parent: Some(Box::new(Window {
+            title: "root".into(),
+            width: 1,
+            scale: 1.0,
+            mask: Hex(0),
+            big: -1,
+            tags: vec![],
+            pos: (0, 0),
+            mixed: (String::new(), 0),
+            parent: None,
+            mode: Mode::Custom { rate: 60 },
+            extra: BTreeMap::new(),
+        })),
+        mode: Mode::Split(0.5, 0.25),
+        extra: BTreeMap::from([("vsync".into(), true)]),
+    }
+}
+
+#[test]
+fn test_to_value_matches_text() {
+    let window = window();
+    let value = to_value(&window).unwrap();
+
+    let text = libconfig_rs::to_string(&window).unwrap();
+    assert_eq!(value, Value::from_str(&text).unwrap());
+
+    assert_eq!(value.lookup_value::<f64>("scale"), Ok(0.1));
+    assert_eq!(value["tags"], libconfig!(("a", "b")));
+    assert_eq!(value["pos"], libconfig!([1, 2]));
+    assert_eq!(value["mode"], libconfig!({ Split: [0.5, 0.25]; }));
+}
+
+#[test]
+fn test_from_value() {
+    let window = window();
+    let mut value = to_value(&window).unwrap();
+    assert_eq!(from_value_ref::<Window>(&value).unwrap(), window);
+
+    value.set_path("width", Value::from(1024)).unwrap();
+    value
+        .set_path("mode", libconfig!({ Fullscreen: (2); }))
+        .unwrap();
+    let patched: Window = from_value(value.clone()).unwrap();
+    assert_eq!(patched.width, 1024);
+    assert_eq!(patched.mode, Mode::Fullscreen(2));
+
+    let err = from_value::<Window>(libconfig!({ title: 1; })).unwrap_err();
+    assert!(!err.to_string().is_empty());
+
+    let from_deserializer = Window::deserialize(value.into_deserializer()).unwrap();
+    assert_eq!(from_deserializer, patched);
+}
+
+#[test]
+fn test_map_keys() {
+    let map = BTreeMap::from([(1, "one")]);
+    assert!(to_value(&map).is_err());
+
+    let map = BTreeMap::from([("one", 1)]);
+    assert_eq!(to_value(&map).unwrap(), libconfig!({ one: 1; }));
+}
+
+#[test]
+fn test_u64_range() {
+    let max = i64::MAX as u64;
+    let value = to_value(&max).unwrap();
+    assert_eq!(
+        value,
+        Value::Int64(i64::MAX, libconfig_rs::IntFormat::Decimal)
+    );
+    assert_eq!(from_value::<u64>(value).unwrap(), max);
+
+    assert!(to_value(&u64::MAX).is_err());
+    assert!(libconfig_rs::to_string(&u64::MAX).is_err());
+}
@@ -0,0 +1,97 @@
+use libconfig_rs::{ArrayType, IntFormat, Value, ViolationKind, libconfig};
+
+#[test]
+fn test_valid() {
+    let value = Value::from_document_str(
+        r#"
+        name = "x";
+        sizes = [1, 2, 3];
+        mixed = ( 1, "two", { three = 3; }, [4L] );
+        *wild-card_1 = { empty = []; };
+        "#,
+    )
+    .unwrap();
+    assert_eq!(value.validate(), Ok(()));
+}
+
+#[test]
+fn test_array_rules() {
+    let mut value = libconfig!({
+        ints: [1, 2];
+        window: {
+            sizes: [1, "two", 3.0];
+        };
+    });
+    value["ints"]
+        .as_vec_mut()
+        .unwrap()
+        .push(libconfig!({ a: 1; }));
+
+    let violations = value.validate().unwrap_err();
+    let found = violations
+        .iter()
+        .map(|v| (v.path(), v.kind().clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        vec![
+            (
+                "ints[2]",
+                ViolationKind::NonScalarInArray { actual: "group" }
+            ),
+            (
+                "window.sizes[1]",
+                ViolationKind::MixedArray {
+                    expected: "int",
+                    actual: "string"
+                }
+            ),
+            (
+                "window.sizes[2]",
+                ViolationKind::MixedArray {
+                    expected: "int",
+                    actual: "float"
+                }
+            ),
+        ]
+    );
+    assert_eq!(
+        violations[1].to_string(),
+        "\"window.sizes[1]\": array of int holds a string"
+    );
+}
+
+#[test]
+fn test_names() {
+    let value = libconfig!({
+        "1st": 1;
+        "with space": { "": true; };
+        ok_name: 2;
+    });
+    let violations = value.validate().unwrap_err();
+    let paths = violations.iter().map(|v| v.path()).collect::<Vec<_>>();
+    assert_eq!(paths, vec!["1st", "with space", "with space."]);
+    assert!(
+        violations
+            .iter()
+            .all(|v| *v.kind() == ViolationKind::InvalidName)
+    );
+}
+
+#[test]
+fn test_int_range() {
+    let value = Value::Array(
+        vec![
+            Value::UInt64(u64::MAX, IntFormat::Hex),
+            Value::UInt64(5, IntFormat::Decimal),
+        ],
+        ArrayType::List,
+    );
+    let violations = value.validate().unwrap_err();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].path(), "[0]");
+    assert_eq!(
+        violations[0].to_string(),
+        "\"[0]\": 18446744073709551615 is out of range for int64"
+    );
+}
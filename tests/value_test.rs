@@ -1,4 +1,7 @@
-use libconfig_rs::{ArrayType, Value};
+use libconfig_rs::{
+    ArrayType, Assignment, EscapeStyle, FormatOptions, Indent, IntFormat, IntegerOverflow,
+    ParseOptions, Terminator, Value, libconfig,
+};
 use std::str::FromStr;
 
 #[test]
@@ -19,14 +22,14 @@ fn test_bool() {
 fn test_int() {
     let config = "config : 123;";
     let res = Value::from_str(config).unwrap();
-    assert_eq!(res, Value::Int(123))
+    assert_eq!(res, Value::Int(123, IntFormat::Decimal))
 }
 
 #[test]
 fn test_long() {
     let config = "config : 4000000L;";
     let res = Value::from_str(config).unwrap();
-    assert_eq!(res, Value::Int(4000000))
+    assert_eq!(res, Value::Int64(4000000, IntFormat::Decimal))
 }
 
 #[test]
@@ -56,7 +59,7 @@ fn test_object() {
     let res = Value::from_str(config).unwrap();
 
     let mut inner = indexmap::IndexMap::new();
-    inner.insert("test".into(), Value::Int(123));
+    inner.insert("test".into(), Value::Int(123, IntFormat::Decimal));
 
     assert_eq!(res, Value::Object(inner))
 }
@@ -81,7 +84,11 @@ fn test_list() {
     inner.insert(
         "test".into(),
         Value::Array(
-            vec![Value::Int(1), Value::Int(2), Value::Int(3)],
+            vec![
+                Value::Int(1, IntFormat::Decimal),
+                Value::Int(2, IntFormat::Decimal),
+                Value::Int(3, IntFormat::Decimal),
+            ],
             ArrayType::List,
         ),
     );
@@ -98,7 +105,11 @@ fn test_array() {
     inner.insert(
         "test".into(),
         Value::Array(
-            vec![Value::Int(1), Value::Int(2), Value::Int(3)],
+            vec![
+                Value::Int(1, IntFormat::Decimal),
+                Value::Int(2, IntFormat::Decimal),
+                Value::Int(3, IntFormat::Decimal),
+            ],
             ArrayType::Array,
         ),
     );
@@ -119,3 +130,534 @@ fn test_vproj_2() {
     let res = Value::from_str(config).unwrap();
     assert!(matches!(res, Value::Object(_)))
 }
+
+#[test]
+fn test_document() {
+    let config = r#"
+        version = "1.0";
+        application : { window : { width = 800; }; };
+        enabled = true;
+    "#;
+    let res = Value::from_document_str(config).unwrap();
+
+    let mut window = indexmap::IndexMap::new();
+    window.insert("width".into(), Value::Int(800, IntFormat::Decimal));
+    let mut application = indexmap::IndexMap::new();
+    application.insert("window".into(), Value::Object(window));
+    let mut settings = indexmap::IndexMap::new();
+    settings.insert("version".into(), Value::String("1.0".into()));
+    settings.insert("application".into(), Value::Object(application));
+    settings.insert("enabled".into(), Value::Bool(true));
+
+    assert_eq!(res, Value::Object(settings));
+}
+
+#[test]
+fn test_document_empty() {
+    let res = Value::from_document_str("  \n").unwrap();
+    assert_eq!(res, Value::Object(indexmap::IndexMap::new()));
+}
+
+#[test]
+fn test_document_trailing_garbage() {
+    assert!(Value::from_document_str("a = 1; }").is_err());
+}
+
+#[test]
+fn test_document_round_trip() {
+    let config = "a : 1;\nb : {\n    c : [ 1, 2 ];\n};\n";
+    let res = Value::from_document_str(config).unwrap();
+    let printed = res.to_document_string().unwrap();
+    assert_eq!(printed, config);
+    assert_eq!(Value::from_document_str(&printed).unwrap(), res);
+}
+
+#[test]
+fn test_document_string_requires_group() {
+    assert!(
+        Value::Int(1, IntFormat::Decimal)
+            .to_document_string()
+            .is_none()
+    );
+}
+
+#[test]
+fn test_display() {
+    let config = "config : { test : 123; };";
+    let res = Value::from_str(config).unwrap();
+    assert_eq!(res.to_string(), "test : 123;\n");
+    assert_eq!(Value::from_document_str(&res.to_string()).unwrap(), res);
+
+    let res = Value::from_str("config : [ 1, 2 ];").unwrap();
+    assert_eq!(res.to_string(), "[ 1, 2 ]");
+}
+
+#[test]
+fn test_comments() {
+    let config = r#"# Leading hash comment
+        // Leading line comment
+        /* Leading
+           block comment */
+        config /* before separator */ : /* before value */ {
+            a = 1; # after setting
+            b // before separator
+                = [ /* first */ 1, // after element
+                    2 /* before close */ ];
+            c : ( "x" /* in list */, { d = true /* before terminator */ ; } );
+            /* before close */
+        } // before terminator
+        ;
+        # Trailing comment without newline"#;
+    let res = Value::from_str(config).unwrap();
+
+    let mut d = indexmap::IndexMap::new();
+    d.insert("d".into(), Value::Bool(true));
+    let mut inner = indexmap::IndexMap::new();
+    inner.insert("a".into(), Value::Int(1, IntFormat::Decimal));
+    inner.insert(
+        "b".into(),
+        Value::Array(
+            vec![
+                Value::Int(1, IntFormat::Decimal),
+                Value::Int(2, IntFormat::Decimal),
+            ],
+            ArrayType::Array,
+        ),
+    );
+    inner.insert(
+        "c".into(),
+        Value::Array(
+            vec![Value::String("x".into()), Value::Object(d)],
+            ArrayType::List,
+        ),
+    );
+
+    assert_eq!(res, Value::Object(inner))
+}
+
+#[test]
+fn test_comment_markers_in_string() {
+    let config = r##"config : "# not // a /* comment */";"##;
+    let res = Value::from_str(config).unwrap();
+    assert_eq!(res, Value::String("# not // a /* comment */".into()))
+}
+
+#[test]
+fn test_comment_only_document() {
+    let res = Value::from_document_str("# nothing here\n/* at all */").unwrap();
+    assert_eq!(res, Value::Object(indexmap::IndexMap::new()));
+}
+
+#[test]
+fn test_unterminated_block_comment() {
+    assert!(Value::from_str("config : { a = 1; /* never closed };").is_err());
+    assert!(Value::from_document_str("a = 1;\n/* never closed").is_err());
+}
+
+#[test]
+fn test_int_radix() {
+    let res = Value::from_document_str(
+        "hex = 0xFF; upper = 0X1e; bin = 0b1010; oct = 0o17; neg = 0xFFFFFFF0; long = 0x10L;",
+    )
+    .unwrap();
+    let expected = [
+        ("hex", Value::Int(255, IntFormat::Hex)),
+        ("upper", Value::Int(30, IntFormat::Hex)),
+        ("bin", Value::Int(10, IntFormat::Binary)),
+        ("oct", Value::Int(15, IntFormat::Octal)),
+        ("neg", Value::Int(-16, IntFormat::Hex)),
+        ("long", Value::Int64(16, IntFormat::Hex)),
+    ];
+    for (name, value) in expected {
+        assert_eq!(res.as_obj().unwrap()[name], value, "{name}");
+    }
+}
+
+#[test]
+fn test_int_radix_round_trip() {
+    let config = "a : 0xFF;\nb : 0b101;\nc : 0o17;\nd : 0xFFFFFFF0;\ne : 12;\n";
+    let res = Value::from_document_str(config).unwrap();
+    assert_eq!(res.to_document_string().unwrap(), config);
+}
+
+#[test]
+fn test_twos_complement_radix() {
+    let config = "a : 0xFFFFFFFF;\nb : 0x80000000;\nc : 0xFFFFFFFFFFFFFFFFL;\nd : 0x100000000L;\n\
+                  e : 0b11111111111111111111111111111110;\nf : 0o37777777777;\n";
+    let res = Value::from_document_str(config).unwrap();
+    let o = res.as_obj().unwrap();
+    assert_eq!(o["a"], Value::Int(-1, IntFormat::Hex));
+    assert_eq!(o["b"], Value::Int(i32::MIN, IntFormat::Hex));
+    assert_eq!(o["c"], Value::Int64(-1, IntFormat::Hex));
+    assert_eq!(o["d"], Value::Int64(0x100000000, IntFormat::Hex));
+    assert_eq!(o["e"], Value::Int(-2, IntFormat::Binary));
+    assert_eq!(o["f"], Value::Int(-1, IntFormat::Octal));
+
+    let printed = res.to_document_string().unwrap();
+    assert_eq!(printed, config);
+    assert_eq!(Value::from_document_str(&printed).unwrap(), res);
+
+    let negative = Value::Object(indexmap::IndexMap::from([
+        ("a".to_string(), Value::Int(-16, IntFormat::Hex)),
+        ("b".to_string(), Value::Int64(-2, IntFormat::Hex)),
+    ]));
+    let printed = negative.to_document_string().unwrap();
+    assert_eq!(printed, "a : 0xFFFFFFF0;\nb : 0xFFFFFFFFFFFFFFFEL;\n");
+    assert_eq!(Value::from_document_str(&printed).unwrap(), negative);
+}
+
+#[test]
+fn test_signed_radix() {
+    for config in ["a = -0x10;", "a = +0x10;", "a = -0b1;", "a = -0o7;"] {
+        let err = Value::from_document_str(config).unwrap_err();
+        assert_eq!(
+            err.reason(),
+            Some("hex, binary and octal integers cannot have a sign"),
+            "{config}"
+        );
+        assert_eq!((err.line(), err.column()), (1, 5));
+    }
+}
+
+#[test]
+fn test_float_not_radix() {
+    let res = Value::from_document_str("a = 1e5; b = 0.5; c = 0e1;").unwrap();
+    let o = res.as_obj().unwrap();
+    assert_eq!(o["a"], Value::Float(1e5));
+    assert_eq!(o["b"], Value::Float(0.5));
+    assert_eq!(o["c"], Value::Float(0.0));
+}
+
+#[test]
+fn test_bad_radix_digits() {
+    assert!(Value::from_str("config : 0b102;").is_err());
+    assert!(Value::from_str("config : 0xG;").is_err());
+}
+
+#[test]
+fn test_int_width() {
+    let res = Value::from_document_str(
+        "max = 2147483647; min = -2147483648; above = 2147483648; below = -2147483649; \
+         wide = 0xFFFFFFFF; ll = 5LL;",
+    )
+    .unwrap();
+    let o = res.as_obj().unwrap();
+    assert_eq!(o["max"], Value::Int(i32::MAX, IntFormat::Decimal));
+    assert_eq!(o["min"], Value::Int(i32::MIN, IntFormat::Decimal));
+    assert_eq!(o["above"], Value::Int64(2147483648, IntFormat::Decimal));
+    assert_eq!(o["below"], Value::Int64(-2147483649, IntFormat::Decimal));
+    assert_eq!(o["wide"], Value::Int(-1, IntFormat::Hex));
+    assert_eq!(o["ll"], Value::Int64(5, IntFormat::Decimal));
+    assert_eq!(o["max"].as_int(), Some(&i32::MAX));
+    assert_eq!(o["ll"].as_int64(), Some(&5));
+    assert_eq!(o["ll"].as_int(), None);
+}
+
+#[test]
+fn test_int64_round_trip() {
+    let config = "a : 1;\nb : 1L;\nc : 0xFFL;\nd : 3000000000L;\n";
+    let res = Value::from_document_str(config).unwrap();
+    assert_eq!(res.to_document_string().unwrap(), config);
+}
+
+#[test]
+fn test_int64_limits() {
+    let res =
+        Value::from_document_str("a = 9223372036854775807; b = -9223372036854775808;").unwrap();
+    let o = res.as_obj().unwrap();
+    assert_eq!(o["a"], Value::Int64(i64::MAX, IntFormat::Decimal));
+    assert_eq!(o["b"], Value::Int64(i64::MIN, IntFormat::Decimal));
+}
+
+#[test]
+fn test_integer_overflow_policy() {
+    let fits_u64 = "a = 18446744073709551615; b = 0xFFFFFFFFFFFFFFFFL;";
+    let config = format!("{fits_u64} c = 18446744073709551616;");
+
+    let options = ParseOptions::new().integer_overflow(IntegerOverflow::U64);
+    assert!(Value::from_document_str_with_options(&config, &options).is_err());
+    let res = Value::from_document_str_with_options(fits_u64, &options).unwrap();
+    let o = res.as_obj().unwrap();
+    assert_eq!(o["a"], Value::UInt64(u64::MAX, IntFormat::Decimal));
+    assert_eq!(o["b"], Value::Int64(-1, IntFormat::Hex));
+    assert_eq!(
+        res.to_document_string().unwrap(),
+        "a : 18446744073709551615L;\nb : 0xFFFFFFFFFFFFFFFFL;\n"
+    );
+
+    let options = ParseOptions::new().integer_overflow(IntegerOverflow::Float);
+    let res = Value::from_document_str_with_options(&config, &options).unwrap();
+    let o = res.as_obj().unwrap();
+    assert_eq!(o["a"], Value::Float(18446744073709551615.0));
+    assert_eq!(o["b"], Value::Int64(-1, IntFormat::Hex));
+    assert_eq!(o["c"], Value::Float(18446744073709551616.0));
+}
+
+#[test]
+fn test_adjacent_strings() {
+    let config = r#"
+        path = "/usr/" "local/" "share";
+        query = "SELECT *"   # columns
+                " FROM t"    /* table */
+                // filter
+                " WHERE id = 1";
+        list = ( "a" "b", "c" );
+    "#;
+    let res = Value::from_document_str(config).unwrap();
+    let o = res.as_obj().unwrap();
+    assert_eq!(o["path"], Value::String("/usr/local/share".into()));
+    assert_eq!(
+        o["query"],
+        Value::String("SELECT * FROM t WHERE id = 1".into())
+    );
+    assert_eq!(
+        o["list"],
+        Value::Array(
+            vec![Value::String("ab".into()), Value::String("c".into())],
+            ArrayType::List
+        )
+    );
+}
+
+#[test]
+fn test_split_long_strings() {
+    let mut settings = indexmap::IndexMap::new();
+    settings.insert(
+        "query".into(),
+        Value::String("SELECT name FROM users WHERE id = 1".into()),
+    );
+    settings.insert("short".into(), Value::String("ok".into()));
+    let value = Value::Object(settings);
+
+    let options = FormatOptions::new().split_strings(16);
+    let res = value.to_document_string_with_options(&options).unwrap();
+    assert_eq!(
+        res,
+        "query : \"SELECT name \"\n    \"FROM users \"\n    \"WHERE id = 1\";\nshort : \"ok\";\n"
+    );
+    assert_eq!(Value::from_document_str(&res).unwrap(), value);
+
+    // Without a space to break at, pieces are cut at the width.
+    let mut settings = indexmap::IndexMap::new();
+    settings.insert("a".into(), Value::String("abcdefgh".into()));
+    let res = Value::Object(settings)
+        .to_document_string_with_options(&FormatOptions::new().split_strings(3))
+        .unwrap();
+    assert_eq!(res, "a : \"abc\"\n    \"def\"\n    \"gh\";\n");
+}
+
+#[test]
+fn test_hex_escapes() {
+    let res = Value::from_str(r#"config : "\x41\x62c \xC3\xA9 \x1b[0m";"#).unwrap();
+    assert_eq!(res, Value::String("Abc é \u{1b}[0m".into()));
+
+    let err = Value::from_str(r#"config : "bad \xFF";"#).unwrap_err();
+    assert_eq!(err.column(), 10);
+    assert_eq!(err.reason(), Some("string escapes do not form valid UTF-8"));
+
+    assert!(Value::from_str(r#"config : "\x4";"#).is_err());
+}
+
+#[test]
+fn test_libconfig_escapes() {
+    let mut settings = indexmap::IndexMap::new();
+    settings.insert(
+        "s".into(),
+        Value::String("tab\tquote\"back\\slash\u{1b}[0m\0\u{7f}\u{c}é".into()),
+    );
+    let value = Value::Object(settings);
+
+    let options = FormatOptions::new().escapes(EscapeStyle::Libconfig);
+    let res = value.to_document_string_with_options(&options).unwrap();
+    assert_eq!(
+        res,
+        "s : \"tab\\tquote\\\"back\\\\slash\\x1B[0m\\x00\\x7F\\fé\";\n"
+    );
+    assert_eq!(Value::from_document_str(&res).unwrap(), value);
+}
+
+#[test]
+fn test_terminators() {
+    let config = "a = 1; b = 2, c = 3\nd = { e = 4, f = 5 }\ng = \"x\"";
+    let res = Value::from_document_str(config).unwrap();
+    let o = res.as_obj().unwrap();
+    assert_eq!(o.len(), 5);
+    assert_eq!(o["c"], Value::Int(3, IntFormat::Decimal));
+    assert_eq!(o["d"].as_obj().unwrap().len(), 2);
+    assert_eq!(o["g"], Value::String("x".into()));
+
+    let res = Value::from_str("config : { a = 1 }").unwrap();
+    assert_eq!(
+        res.as_obj().unwrap()["a"],
+        Value::Int(1, IntFormat::Decimal)
+    );
+}
+
+#[test]
+fn test_trailing_commas() {
+    let res = Value::from_document_str("a = [1, 2,]; b = (\"x\", );").unwrap();
+    let o = res.as_obj().unwrap();
+    assert_eq!(o["a"].as_vec().unwrap().len(), 2);
+    assert_eq!(o["b"].as_vec().unwrap().len(), 1);
+
+    assert!(Value::from_document_str("a = [,];").is_err());
+    assert!(Value::from_document_str("a = [1,,];").is_err());
+    assert!(Value::from_document_str("a = 1;;").is_err());
+}
+
+#[test]
+fn test_print_terminators() {
+    let value = Value::from_document_str("a = 1; b = { c = [1, 2]; };").unwrap();
+
+    let options = FormatOptions::new().terminator(Terminator::Omit);
+    let res = value.to_document_string_with_options(&options).unwrap();
+    assert_eq!(res, "a : 1\nb : {\n    c : [ 1, 2 ]\n}\n");
+    assert_eq!(Value::from_document_str(&res).unwrap(), value);
+
+    let options = FormatOptions::new().terminator(Terminator::Comma);
+    let res = value.to_document_string_with_options(&options).unwrap();
+    assert_eq!(res, "a : 1,\nb : {\n    c : [ 1, 2 ],\n},\n");
+    assert_eq!(Value::from_document_str(&res).unwrap(), value);
+}
+
+#[test]
+fn test_strict_terminators() {
+    let options = ParseOptions::new().strict_terminators(true);
+    let strict = |config| Value::from_document_str_with_options(config, &options);
+
+    assert!(strict("a = 1; b = { c = 2, };").is_ok());
+    let err = strict("a = 1; b = { c = 2 };").unwrap_err();
+    assert_eq!(err.column(), 20);
+    assert_eq!(err.expected(), ["','", "';'"]);
+    assert!(strict("a = 1\nb = 2;").is_err());
+}
+
+#[test]
+fn test_bool_case() {
+    let res = Value::from_document_str("a = TRUE; b = False;").unwrap();
+    assert_eq!(res, libconfig!({ a: true; b: false; }));
+
+    let options = ParseOptions::new().case_sensitive_bools(true);
+    assert!(Value::from_document_str_with_options("a = true; b = false;", &options).is_ok());
+    assert!(Value::from_document_str_with_options("a = TRUE;", &options).is_err());
+    assert!(Value::from_str_with_options("config : { a = False; };", &options).is_err());
+}
+
+#[test]
+fn test_allow_overrides() {
+    let options = ParseOptions::new().allow_overrides(true);
+    let res = Value::from_document_str_with_options("a = 1; a = 2;", &options).unwrap();
+    assert_eq!(res, libconfig!({ a: 2; }));
+
+    let options = options.allow_overrides(false);
+    assert!(Value::from_document_str_with_options("a = 1; a = 2;", &options).is_err());
+}
+
+#[test]
+fn test_format_options() {
+    let value = libconfig!({
+        name: "x";
+        window: { size: [1.5, 2.0]; title: { text: "t"; }; };
+    });
+
+    let options = FormatOptions::new()
+        .indent(Indent::Tabs)
+        .assignment(Assignment::Equals)
+        .colon_for_groups(true)
+        .brace_on_own_line(true)
+        .float_precision(2)
+        .trailing_newline(false);
+    let res = value.to_document_string_with_options(&options).unwrap();
+    assert_eq!(
+        res,
+        "name = \"x\";\nwindow :\n{\n\tsize = [ 1.50, 2.00 ];\n\ttitle :\n\t{\n\t\ttext = \"t\";\n\t};\n};"
+    );
+    assert_eq!(Value::from_document_str(&res).unwrap(), value);
+
+    let options = FormatOptions::new()
+        .indent(Indent::Spaces(2))
+        .assignment(Assignment::Equals)
+        .float_precision(0);
+    let res = value.to_document_string_with_options(&options).unwrap();
+    assert_eq!(
+        res,
+        "name = \"x\";\nwindow = {\n  size = [ 2.0, 2.0 ];\n  title = {\n    text = \"t\";\n  };\n};\n"
+    );
+}
+
+#[test]
+fn test_float_precision_exponent() {
+    let value = Value::from_document_str("a = [1e300, 1e-5, -12345678901.5, 0.0];").unwrap();
+
+    let options = FormatOptions::new().float_precision(2);
+    let res = value.to_document_string_with_options(&options).unwrap();
+    assert_eq!(res, "a : [ 1.00e300, 1.00e-5, -1.23e10, 0.00 ];\n");
+    assert_eq!(
+        Value::from_document_str(&res).unwrap(),
+        Value::from_document_str("a = [1e300, 1e-5, -1.23e10, 0.0];").unwrap()
+    );
+
+    let options = FormatOptions::new().float_precision(0);
+    let res = value.to_document_string_with_options(&options).unwrap();
+    assert_eq!(res, "a : [ 1e300, 1e-5, -1e10, 0.0 ];\n");
+    assert!(
+        Value::from_document_str(&res).unwrap()["a"][0]
+            .as_float()
+            .is_some()
+    );
+}
+
+#[test]
+fn test_max_width() {
+    let value = Value::from_document_str(
+        r#"
+        short = [1, 2, 3];
+        numbers = [100000, 200000, 300000, 400000, 500000, 600000];
+        scenes = ( { name = "a"; ids = [1, 2]; }, { name = "b"; ids = (); } );
+        nested = ( [1, 2], ("a long string", "another long string", "x") );
+        "#,
+    )
+    .unwrap();
+
+    let options = FormatOptions::new().max_width(40);
+    let res = value.to_document_string_with_options(&options).unwrap();
+    assert_eq!(
+        res,
+        r#"short : [ 1, 2, 3 ];
+numbers : [
+    100000,
+    200000,
+    300000,
+    400000,
+    500000,
+    600000
+];
+scenes : (
+    {
+        name : "a";
+        ids : [ 1, 2 ];
+    },
+    {
+        name : "b";
+        ids : ( );
+    }
+);
+nested : (
+    [ 1, 2 ],
+    (
+        "a long string",
+        "another long string",
+        "x"
+    )
+);
+"#
+    );
+    assert_eq!(Value::from_document_str(&res).unwrap(), value);
+
+    // Laying out the output again changes nothing.
+    let again = Value::from_document_str(&res)
+        .unwrap()
+        .to_document_string_with_options(&options)
+        .unwrap();
+    assert_eq!(again, res);
+}